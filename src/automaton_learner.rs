@@ -0,0 +1,247 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::deterministic_finite_automaton::{AlphaTable, build_dfa_with_command_args, DeterministicFiniteAutomaton, State, TransFunc};
+use crate::r#type::StringArgs;
+use crate::statics::EMPTY_SENTENCE;
+use crate::utils::{collect_ordered_vec, fresh_non_terminal};
+
+pub trait LearningOracle {
+    //成员查询：该串是否属于目标语言
+    fn membership(&self, word: &str) -> bool;
+    //等价查询：假设自动机与目标语言等价时返回None，否则返回一个反例
+    fn equivalence(&self, hypothesis: &DeterministicFiniteAutomaton) -> Option<String>;
+}
+
+impl LearningOracle for DeterministicFiniteAutomaton {
+    fn membership(&self, word: &str) -> bool {
+        self.accept(word)
+    }
+    fn equivalence(&self, hypothesis: &DeterministicFiniteAutomaton) -> Option<String> {
+        self.find_distinguishing_word(hypothesis)//通过乘积自动机判断等价
+    }
+}
+
+pub struct ClosureOracle<F: Fn(&str) -> bool> {
+    membership: F,
+    alpha: AlphaTable,
+    max_len: usize,
+}
+
+impl<F: Fn(&str) -> bool> ClosureOracle<F> {
+    pub fn new(membership: F, alpha: AlphaTable, max_len: usize) -> Self {
+        Self {
+            membership,
+            alpha,
+            max_len,
+        }
+    }
+}
+
+impl<F: Fn(&str) -> bool> LearningOracle for ClosureOracle<F> {
+    fn membership(&self, word: &str) -> bool {
+        (self.membership)(word)
+    }
+    fn equivalence(&self, hypothesis: &DeterministicFiniteAutomaton) -> Option<String> {
+        //闭包无法精确判断等价，按长度从小到大枚举不超过max_len的所有串进行比对
+        let alphas = collect_ordered_vec(self.alpha.iter().copied());
+        let mut layer = vec![String::new()];
+        for len in 0..=self.max_len {
+            if let Some(word) = layer.iter().find(|word| (self.membership)(word) != hypothesis.accept(word)) {
+                return Some(word.clone());
+            }
+            if len == self.max_len {
+                break;
+            }
+            layer = layer.iter().flat_map(|word| alphas.iter().map(move |alpha| format!("{word}{alpha}"))).collect();
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObservationTable {
+    prefixes: Vec<String>, //S 前缀集合
+    suffixes: Vec<String>, //E 后缀集合
+    alphas: Vec<char>, //字母表
+    entries: HashMap<String, bool>, //T 对每个查询过的串记录成员查询的结果
+}
+
+impl ObservationTable {
+    fn new(alpha: &AlphaTable) -> Self {
+        Self {
+            prefixes: vec![String::new()],
+            suffixes: vec![String::new()],
+            alphas: collect_ordered_vec(alpha.iter().copied()),
+            entries: HashMap::new(),
+        }
+    }
+    fn fill<O: LearningOracle>(&mut self, oracle: &O) {
+        //对 (S ∪ S·Σ)·E 中尚未查询的串进行成员查询
+        let rows = self.prefixes.iter().flat_map(|s| {
+            std::iter::once(s.clone()).chain(self.alphas.iter().map(move |a| format!("{s}{a}")))
+        }).collect::<Vec<_>>();
+        for row in rows {
+            for suffix in &self.suffixes {
+                let word = format!("{row}{suffix}");
+                self.entries.entry(word.clone()).or_insert_with(|| oracle.membership(&word));
+            }
+        }
+    }
+    fn row(&self, prefix: &str) -> Vec<bool> {
+        self.suffixes.iter().map(|suffix| self.entries[&format!("{prefix}{suffix}")]).collect()
+    }
+    fn find_unclosed(&self) -> Option<String> {
+        //寻找 s·a 使得 row(s·a) 不等于任何 row(s')
+        let rows = self.prefixes.iter().map(|s| self.row(s)).collect::<HashSet<_>>();
+        self.prefixes.iter()
+            .flat_map(|s| self.alphas.iter().map(move |a| format!("{s}{a}")))
+            .find(|extended| !rows.contains(&self.row(extended)))
+    }
+    fn find_inconsistent(&self) -> Option<String> {
+        //寻找 row(s1)=row(s2) 但 row(s1·a)≠row(s2·a) 的情况，返回需要加入E的新后缀 a·e
+        for (index, s1) in self.prefixes.iter().enumerate() {
+            for s2 in self.prefixes.iter().skip(index + 1) {
+                if self.row(s1) != self.row(s2) {
+                    continue;
+                }
+                for a in &self.alphas {
+                    let (s1a, s2a) = (format!("{s1}{a}"), format!("{s2}{a}"));
+                    if let Some(suffix) = self.suffixes.iter().find(|e| {
+                        self.entries[&format!("{s1a}{e}")] != self.entries[&format!("{s2a}{e}")]
+                    }) {
+                        return Some(format!("{a}{suffix}"));
+                    }
+                }
+            }
+        }
+        None
+    }
+    fn build_hypothesis(&self) -> Result<DeterministicFiniteAutomaton, String> {
+        //每个不同的行对应一个状态，状态依次命名为A,B,C...，大写字母用尽后使用其他Unicode大写字母
+        let mut row_state = HashMap::new();
+        for prefix in &self.prefixes {
+            let row = self.row(prefix);
+            if !row_state.contains_key(&row) {
+                let used = row_state.values().copied().collect();
+                let state = fresh_non_terminal(&used).ok_or("状态数过多，无法命名")?;
+                row_state.insert(row, state);
+            }
+        }
+        let (mut end_state_set, mut trans) = (HashSet::new(), HashMap::new());
+        for prefix in &self.prefixes {
+            let state: State = row_state[&self.row(prefix)];
+            if self.entries[prefix] {
+                end_state_set.insert(state);
+            }
+            for &a in &self.alphas {
+                trans.insert(TransFunc::new(state, a), row_state[&self.row(&format!("{prefix}{a}"))]);
+            }
+        }
+        DeterministicFiniteAutomaton::build(
+            self.alphas.iter().copied().collect(),
+            row_state.values().copied().collect(),
+            row_state[&self.row("")],
+            end_state_set,
+            trans,
+        ).map_err(|_| "构造假设自动机失败".to_string())
+    }
+}
+
+impl Display for ObservationTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |word: &str| if word.is_empty() { EMPTY_SENTENCE.to_string() } else { word.to_string() };
+        let width = self.prefixes.iter().map(|s| s.len() + 1).chain(self.suffixes.iter().map(|e| e.len())).max().unwrap_or(1).max(2);
+        write!(f, "{:width$} |", "")?;
+        for suffix in &self.suffixes {
+            write!(f, " {:width$}", show(suffix))?;
+        }
+        writeln!(f)?;
+        let extended = self.prefixes.iter()
+            .flat_map(|s| self.alphas.iter().map(move |a| format!("{s}{a}")))
+            .filter(|x| !self.prefixes.contains(x))
+            .collect::<Vec<_>>();
+        for (index, row) in self.prefixes.iter().chain(extended.iter()).enumerate() {
+            if index == self.prefixes.len() {
+                writeln!(f, "{}", "-".repeat((width + 1) * (self.suffixes.len() + 1) + 1))?;//分隔S与S·Σ
+            }
+            write!(f, "{:width$} |", show(row))?;
+            for value in self.row(row) {
+                write!(f, " {:width$}", if value { 1 } else { 0 })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct LStarLearner {
+    alpha: AlphaTable,
+    max_rounds: usize,
+}
+
+impl LStarLearner {
+    pub fn new(alpha: AlphaTable) -> Self {
+        Self {
+            alpha,
+            max_rounds: LStarLearner::DEFAULT_MAX_ROUNDS,
+        }
+    }
+    pub fn learn<O: LearningOracle>(&self, oracle: &O) -> Result<(DeterministicFiniteAutomaton, Vec<String>), String> {
+        let mut dumps = Vec::new();
+        let mut table = ObservationTable::new(&self.alpha);
+        table.fill(oracle);
+        for round in 1..=self.max_rounds {
+            loop {//使观察表闭合且一致
+                if let Some(extended) = table.find_unclosed() {
+                    table.prefixes.push(extended);
+                } else if let Some(suffix) = table.find_inconsistent() {
+                    table.suffixes.push(suffix);
+                } else {
+                    break;
+                }
+                table.fill(oracle);
+            }
+            let hypothesis = table.build_hypothesis()?;
+            dumps.push(format!("第{round}轮观察表如下\n{table}"));
+            match oracle.equivalence(&hypothesis) {
+                None => {
+                    dumps.push(format!("第{round}轮假设自动机被接受，共{}个状态", hypothesis.state().len()));
+                    return Ok((hypothesis, dumps));
+                }
+                Some(counter) => {
+                    dumps.push(format!("第{round}轮得到反例 {}", if counter.is_empty() { EMPTY_SENTENCE } else { &counter }));
+                    //将反例的所有前缀加入S
+                    for end in counter.char_indices().map(|(index, _)| index).skip(1).chain([counter.len()]) {
+                        let prefix = counter[..end].to_string();
+                        if !table.prefixes.contains(&prefix) {
+                            table.prefixes.push(prefix);
+                        }
+                    }
+                    table.fill(oracle);
+                }
+            }
+        }
+        Err(format!("在{}轮内未能学习到目标自动机", self.max_rounds))
+    }
+}
+
+pub fn learn_dfa_with_command_args(mut args: StringArgs) -> Result<(DeterministicFiniteAutomaton, Vec<String>), String> {
+    //以命令行给出的DFA作为黑盒进行学习
+    //若给出--max_len 则只允许成员查询，等价查询退化为枚举不超过该长度的串
+    let (mut dfa_args, mut max_len) = (Vec::new(), None);
+    while let Some(mode) = args.next() {
+        if mode == "--max_len" {
+            let val = args.next().ok_or("excepted value of param --max_len")?;
+            max_len = Some(val.parse::<usize>().map_err(|_| "--max_len 需要为非负整数".to_string())?);
+        } else {
+            dfa_args.push(mode);
+        }
+    }
+    let target = build_dfa_with_command_args(dfa_args.into_iter());
+    let learner = LStarLearner::new(target.alpha().clone());
+    match max_len {
+        Some(max_len) => learner.learn(&ClosureOracle::new(|word: &str| target.accept(word), target.alpha().clone(), max_len)),
+        None => learner.learn(&target),
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::statics::{EMPTY_SENTENCE, GRAMMAR_SPLIT_IO_UNIT, GRAMMAR_SPLIT_TARGET_UNIT, SPLIT_UNITS};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Linearity {
+    Right, //A->aB|a
+    Left, //A->Ba|a
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ChomskyType {
+    Type0,
+    Type1,
+    Type2,
+    Type3(Linearity),
+}
+
+impl Display for ChomskyType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChomskyType::Type0 => write!(f, "0型文法（短语结构文法）"),
+            ChomskyType::Type1 => write!(f, "1型文法（上下文有关文法）"),
+            ChomskyType::Type2 => write!(f, "2型文法（上下文无关文法）"),
+            ChomskyType::Type3(Linearity::Right) => write!(f, "3型文法（右线性正规文法）"),
+            ChomskyType::Type3(Linearity::Left) => write!(f, "3型文法（左线性正规文法）"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChomskyClassification {
+    grammar_type: ChomskyType,
+    reasons: Vec<String>, //不满足更严格一级文法的原因
+}
+
+impl ChomskyClassification {
+    pub fn classify(grammar_tokens: &str, start: char) -> Result<Self, String> {
+        //产生式左部可以是任意符号串，大写字母为非终结符，例如 S->aSBc|abc,cB->Bc,bB->bb
+        //每个字符都是一个文法符号，与按字符构建文法的分析工具保持一致，因此不接受空白
+        if grammar_tokens.contains(char::is_whitespace) {
+            return Err("文法中含有空白字符，文法分类只支持不以空格分隔的单字符文法符号".to_string());
+        }
+        let mut productions = Vec::new();
+        for production in grammar_tokens.split(SPLIT_UNITS).filter(|x| !x.is_empty()) {
+            let (left, right) = production.split_once(GRAMMAR_SPLIT_IO_UNIT).ok_or(format!("{production} 缺少{GRAMMAR_SPLIT_IO_UNIT}"))?;
+            if !left.chars().any(|x| x.is_ascii_uppercase()) {
+                return Err(format!("{production} 的左部不含非终结符"));
+            }
+            for right in right.split(GRAMMAR_SPLIT_TARGET_UNIT) {
+                if right.is_empty() {
+                    return Err(format!("{production} 含有空的候选式，空串请使用{EMPTY_SENTENCE}"));
+                }
+                productions.push((left.to_string(), right.to_string()));
+            }
+        }
+        if !productions.iter().any(|(left, _)| left == &start.to_string()) {
+            return Err(format!("开始符号{start}没有任何产生式"));
+        }
+        let type1_reasons = ChomskyClassification::type1_violations(&productions, start);
+        let type2_reasons = productions.iter()
+            .filter(|(left, _)| left.chars().count() != 1)
+            .map(|(left, right)| format!("{left}{GRAMMAR_SPLIT_IO_UNIT}{right} 的左部不是单个非终结符"))
+            .collect::<Vec<_>>();
+        let (grammar_type, reasons) = if !type2_reasons.is_empty() {
+            if type1_reasons.is_empty() {
+                (ChomskyType::Type1, type2_reasons)
+            } else {
+                (ChomskyType::Type0, type1_reasons)
+            }
+        } else {
+            match ChomskyClassification::linearity(&productions) {
+                Ok(linearity) => (ChomskyType::Type3(linearity), Vec::new()),
+                Err(reasons) => (ChomskyType::Type2, reasons),
+            }
+        };
+        Ok(ChomskyClassification {
+            grammar_type,
+            reasons,
+        })
+    }
+    fn type1_violations(productions: &[(String, String)], start: char) -> Vec<String> {
+        //|α|<=|β|，仅当开始符号不出现在右部时允许S->$
+        let start_on_right = productions.iter().any(|(_, right)| right.contains(start));
+        productions.iter().filter_map(|(left, right)| {
+            let shown = format!("{left}{GRAMMAR_SPLIT_IO_UNIT}{right}");
+            if right == EMPTY_SENTENCE {
+                if left != &start.to_string() {
+                    Some(format!("{shown} 为非开始符号的空产生式"))
+                } else if start_on_right {
+                    Some(format!("{shown} 为空产生式，但开始符号{start}出现在产生式右部"))
+                } else {
+                    None
+                }
+            } else if left.chars().count() > right.chars().count() {
+                Some(format!("{shown} 的左部比右部长"))
+            } else {
+                None
+            }
+        }).collect()
+    }
+    fn linearity(productions: &[(String, String)]) -> Result<Linearity, Vec<String>> {
+        //每个产生式须形如A->a、A->$，再统一为A->aB（右线性）或A->Ba（左线性）
+        let mut reasons = Vec::new();
+        let (mut right_linear, mut left_linear) = (Vec::new(), Vec::new());
+        for (left, right) in productions {
+            let shown = format!("{left}{GRAMMAR_SPLIT_IO_UNIT}{right}");
+            let symbols = right.chars().collect::<Vec<_>>();
+            match symbols.as_slice() {
+                _ if right == EMPTY_SENTENCE => {}
+                [v_t] if !v_t.is_ascii_uppercase() => {}
+                [v_t, v_n] if !v_t.is_ascii_uppercase() && v_n.is_ascii_uppercase() => right_linear.push(shown),
+                [v_n, v_t] if v_n.is_ascii_uppercase() && !v_t.is_ascii_uppercase() => left_linear.push(shown),
+                _ => reasons.push(format!("{shown} 的右部不是A->a、A->aB或A->Ba的形式")),
+            }
+        }
+        if !right_linear.is_empty() && !left_linear.is_empty() {
+            reasons.push(format!("右线性产生式{}与左线性产生式{}混用", right_linear[0], left_linear[0]));
+        }
+        match (reasons.is_empty(), left_linear.is_empty()) {
+            (true, true) => Ok(Linearity::Right),
+            (true, false) => Ok(Linearity::Left),
+            _ => Err(reasons),
+        }
+    }
+    pub fn grammar_type(&self) -> ChomskyType {
+        self.grammar_type
+    }
+}
+
+impl Display for ChomskyClassification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "该文法是{}", self.grammar_type)?;
+        let stricter = match self.grammar_type {
+            ChomskyType::Type0 => "1型文法",
+            ChomskyType::Type1 => "2型文法",
+            ChomskyType::Type2 => "3型文法",
+            ChomskyType::Type3(_) => return writeln!(f),
+        };
+        writeln!(f, "，不是{stricter}的原因如下")?;
+        self.reasons.iter().try_for_each(|x| writeln!(f, "  {x}"))
+    }
+}
+
+pub fn is_deterministic_right_linear(grammar_tokens: &str) -> bool {
+    //右线性文法中同一非终结符对同一终结符只有一个候选式时，可直接转换为DFA
+    let mut targets: HashMap<(String, char), HashSet<String>> = HashMap::new();
+    for production in grammar_tokens.split(SPLIT_UNITS).filter(|x| !x.is_empty()) {
+        if let Some((left, right)) = production.split_once(GRAMMAR_SPLIT_IO_UNIT) {
+            for right in right.split(GRAMMAR_SPLIT_TARGET_UNIT).filter(|x| *x != EMPTY_SENTENCE) {
+                let mut chars = right.chars();
+                if let Some(v_t) = chars.next() {
+                    targets.entry((left.to_string(), v_t)).or_default().insert(chars.collect());
+                }
+            }
+        }
+    }
+    targets.values().all(|x| x.len() == 1)
+}
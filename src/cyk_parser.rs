@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::io::stdin;
+
+use crate::parse_tree::ParseTree;
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::statics::{EMPTY_SENTENCE, EMPTY_SENTENCE_CHAR};
+use crate::utils::{collect_ordered_vec, split_production};
+
+#[derive(Debug, Clone, Copy)]
+enum CykBackPointer {
+    Terminal(char), //A->a
+    Split(usize, char, char), //A->BC，B推导出前k个字符
+}
+
+#[derive(Debug, Clone)]
+pub struct CykTable {
+    sentence: Vec<char>,
+    cells: Vec<Vec<HashMap<char, CykBackPointer>>>, //cells[len-1][i] 为推导出从第i个字符开始、长度为len的子串的非终结符
+}
+
+impl CykTable {
+    fn build_tree(&self, v_n: char, len: usize, begin: usize) -> ParseTree {
+        match self.cells[len - 1][begin][&v_n] {
+            CykBackPointer::Terminal(v_t) => ParseTree::new(v_n, vec![ParseTree::leaf(v_t)]),
+            CykBackPointer::Split(k, left, right) => ParseTree::new(v_n, vec![
+                self.build_tree(left, k, begin),
+                self.build_tree(right, len - k, begin + k),
+            ]),
+        }
+    }
+}
+
+impl Display for CykTable {
+    //按三角形输出，最下一行为长度为1的子串
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |cell: &HashMap<char, CykBackPointer>| if cell.is_empty() {
+            "∅".to_string()
+        } else {
+            format!("{{{}}}", collect_ordered_vec(cell.keys().copied()).into_iter().map(String::from).collect::<Vec<_>>().join(","))
+        };
+        let width = self.cells.iter().flatten().map(|x| show(x).chars().count()).max().unwrap_or(1).max(1);
+        for (len, row) in self.cells.iter().enumerate().rev() {
+            write!(f, "{:>3} |", len + 1)?;
+            for cell in row {
+                write!(f, " {:width$}", show(cell))?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "    |")?;
+        for ch in &self.sentence {
+            write!(f, " {:width$}", ch)?;
+        }
+        writeln!(f)
+    }
+}
+
+pub struct CykParser {
+    grammar: PushDownAutomatonGrammar,
+    terminal_rules: HashMap<char, HashSet<char>>, //a -> {A | A->a}
+    binary_rules: HashMap<(char, char), HashSet<char>>, //(B,C) -> {A | A->BC}
+}
+
+impl CykParser {
+    pub fn new(grammar: &PushDownAutomatonGrammar) -> Result<Self, String> {
+        grammar.check_single_char_symbols("CYK分析")?;
+        let grammar = if grammar.is_chomsky_normal_form() { grammar.clone() } else { grammar.to_chomsky_normal_form()? };
+        let mut terminal_rules: HashMap<char, HashSet<char>> = HashMap::new();
+        let mut binary_rules: HashMap<(char, char), HashSet<char>> = HashMap::new();
+        for (left_v_n, production_set) in grammar.production_set() {
+            for production in production_set {
+                match split_production(production).as_slice() {
+                    [v_t] => { terminal_rules.entry(*v_t).or_default().insert(*left_v_n); }
+                    [b, c] => { binary_rules.entry((*b, *c)).or_default().insert(*left_v_n); }
+                    _ => {}
+                }
+            }
+        }
+        Ok(CykParser {
+            grammar,
+            terminal_rules,
+            binary_rules,
+        })
+    }
+    pub fn grammar(&self) -> &PushDownAutomatonGrammar {
+        &self.grammar
+    }
+    pub fn parse(&self, sentence: &str) -> (CykTable, Option<ParseTree>) {
+        let sentence = sentence.chars().collect::<Vec<_>>();
+        let n = sentence.len();
+        let mut cells: Vec<Vec<HashMap<char, CykBackPointer>>> = (1..=n).map(|len| vec![HashMap::new(); n + 1 - len]).collect();
+        for (i, v_t) in sentence.iter().enumerate() {
+            for v_n in self.terminal_rules.get(v_t).into_iter().flatten() {
+                cells[0][i].insert(*v_n, CykBackPointer::Terminal(*v_t));
+            }
+        }
+        for len in 2..=n {
+            for begin in 0..=n - len {
+                for k in 1..len {
+                    //子串划分为长度k与len-k的两段
+                    let lefts = collect_ordered_vec(cells[k - 1][begin].keys().copied());
+                    let rights = collect_ordered_vec(cells[len - k - 1][begin + k].keys().copied());
+                    for &b in &lefts {
+                        for &c in &rights {
+                            for &a in self.binary_rules.get(&(b, c)).into_iter().flatten() {
+                                cells[len - 1][begin].entry(a).or_insert(CykBackPointer::Split(k, b, c));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let table = CykTable {
+            sentence,
+            cells,
+        };
+        let start = self.grammar.start();
+        let tree = if n == 0 {
+            self.grammar.production_set().get(&start)
+                .filter(|x| x.contains(EMPTY_SENTENCE))
+                .map(|_| ParseTree::new(start, vec![ParseTree::leaf(EMPTY_SENTENCE_CHAR)]))
+        } else if table.cells[n - 1][0].contains_key(&start) {
+            Some(table.build_tree(start, n, 0))
+        } else {
+            None
+        };
+        (table, tree)
+    }
+}
+
+pub fn test_sentence_using_cyk_parser_cli(parser: &CykParser) {
+    loop {
+        let mut buffer = String::new();
+        stdin().read_line(&mut buffer).expect("读取时发生错误！");
+        if buffer.is_empty() {
+            break;
+        }
+        let sentence = buffer.trim();
+        let sentence = if sentence == EMPTY_SENTENCE { "" } else { sentence };
+        let (table, tree) = parser.parse(sentence);
+        println!("CYK分析表如下");
+        println!("{table}");
+        match tree {
+            Some(tree) => {
+                println!("该字符串是可接受的，语法树如下");
+                println!("{tree}");
+            }
+            None => {
+                println!("该字符串是不可接受的");
+            }
+        }
+    }
+}
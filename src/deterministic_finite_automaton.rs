@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Formatter};
 use std::io::stdin;
 use std::mem::swap;
@@ -290,6 +290,41 @@ impl DeterministicFiniteAutomaton {
     pub fn trans(&self) -> &GrammarFunction {
         &self.trans
     }
+    pub fn accept(&self, word: &str) -> bool {
+        //从开始状态依次转移，缺少转换函数时视为进入死状态
+        let mut now_state = self.start_state;
+        for alpha in word.chars() {
+            match self.trans.get(&TransFunc::new(now_state, alpha)) {
+                Some(next_state) => now_state = *next_state,
+                None => return false,
+            }
+        }
+        self.end_state_set.contains(&now_state)
+    }
+    pub fn find_distinguishing_word(&self, other: &Self) -> Option<String> {
+        //在两个自动机的乘积上广度优先搜索，找到最短的使两者接受情况不同的串
+        //None表示死状态
+        let step = |dfa: &Self, state: Option<State>, alpha: char| {
+            state.and_then(|s| dfa.trans.get(&TransFunc::new(s, alpha)).copied())
+        };
+        let is_end = |dfa: &Self, state: Option<State>| state.is_some_and(|s| dfa.end_state_set.contains(&s));
+        let alphas = collect_ordered_vec(self.alpha.union(&other.alpha).copied());
+        let start = (Some(self.start_state), Some(other.start_state));
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, String::new())]);
+        while let Some(((left, right), word)) = queue.pop_front() {
+            if is_end(self, left) != is_end(other, right) {
+                return Some(word);
+            }
+            for &alpha in &alphas {
+                let next = (step(self, left, alpha), step(other, right, alpha));
+                if visited.insert(next) {
+                    queue.push_back((next, format!("{word}{alpha}")));
+                }
+            }
+        }
+        None
+    }
 }
 
 pub fn build_dfa_with_command_args(mut args: StringArgs) -> DeterministicFiniteAutomaton {
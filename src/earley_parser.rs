@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::io::stdin;
+
+use crate::grammar_symbol::SymbolTable;
+use crate::grammar_transformation::SymbolProductions;
+use crate::parse_tree::ParseTree;
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::statics::{EMPTY_SENTENCE_CHAR, GRAMMAR_SPLIT_IO_UNIT};
+use crate::utils::collect_ordered_vec;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct EarleyItem {
+    left: char,
+    body: Vec<char>,
+    dot: usize, //圆点前已识别的符号个数
+    origin: usize, //该项目开始识别的位置
+}
+
+impl EarleyItem {
+    fn next_symbol(&self) -> Option<char> {
+        self.body.get(self.dot).copied()
+    }
+    fn advance(&self) -> Self {
+        EarleyItem {
+            dot: self.dot + 1,
+            ..self.clone()
+        }
+    }
+}
+
+impl EarleyItem {
+    fn show(&self, symbols: &SymbolTable) -> String {
+        let before = symbols.join(self.body[..self.dot].iter().copied());
+        let after = symbols.join(self.body[self.dot..].iter().copied());
+        format!("[{}{GRAMMAR_SPLIT_IO_UNIT}{before}·{after}, {}]", symbols.name(self.left), self.origin)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EarleyChart {
+    sentence: Vec<char>,
+    symbols: SymbolTable,
+    sets: Vec<Vec<EarleyItem>>, //sets[j] 为读入前j个字符后的项目集
+    completed: HashSet<(char, usize, usize)>, //已完成的项目 (A,i,j) 表示A推导出第i到第j个字符之间的子串
+}
+
+impl Display for EarleyChart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (j, set) in self.sets.iter().enumerate() {
+            let read = self.symbols.show_sequence(self.sentence[..j].iter().copied());
+            writeln!(f, "S{j} (已读入{read}):")?;
+            for item in set {
+                writeln!(f, "    {}", item.show(&self.symbols))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct EarleyParser {
+    grammar: PushDownAutomatonGrammar,
+    productions: SymbolProductions,
+    nullable: HashSet<char>,
+}
+
+impl EarleyParser {
+    pub fn new(grammar: &PushDownAutomatonGrammar) -> Self {
+        EarleyParser {
+            grammar: grammar.clone(),
+            productions: grammar.symbol_productions(),
+            nullable: grammar.get_nullable_set(),
+        }
+    }
+    fn predict_items(&self, v_n: char, origin: usize) -> Vec<EarleyItem> {
+        self.productions.get(&v_n).into_iter().flatten().map(|body| EarleyItem {
+            left: v_n,
+            body: body.clone(),
+            dot: 0,
+            origin,
+        }).collect()
+    }
+    pub fn recognize(&self, sentence: &str) -> EarleyChart {
+        let sentence = sentence.chars().collect::<Vec<_>>();
+        let mut sets: Vec<Vec<EarleyItem>> = vec![Vec::new(); sentence.len() + 1];
+        let mut seen: Vec<HashSet<EarleyItem>> = vec![HashSet::new(); sentence.len() + 1];
+        let mut completed = HashSet::new();
+        let add = |sets: &mut Vec<Vec<EarleyItem>>, seen: &mut Vec<HashSet<EarleyItem>>, j: usize, item: EarleyItem| {
+            if seen[j].insert(item.clone()) {
+                sets[j].push(item);
+            }
+        };
+        for item in self.predict_items(self.grammar.start(), 0) {
+            add(&mut sets, &mut seen, 0, item);
+        }
+        for j in 0..=sentence.len() {
+            let mut index = 0;
+            while index < sets[j].len() {
+                let item = sets[j][index].clone();
+                match item.next_symbol() {
+                    Some(symbol) if self.productions.contains_key(&symbol) => {
+                        //预测：展开圆点后的非终结符，若其可空则直接越过（Aycock-Horspool）
+                        for predicted in self.predict_items(symbol, j) {
+                            add(&mut sets, &mut seen, j, predicted);
+                        }
+                        if self.nullable.contains(&symbol) {
+                            add(&mut sets, &mut seen, j, item.advance());
+                        }
+                    }
+                    Some(symbol) => {
+                        //扫描：圆点后的终结符与当前字符匹配
+                        if sentence.get(j) == Some(&symbol) {
+                            add(&mut sets, &mut seen, j + 1, item.advance());
+                        }
+                    }
+                    None => {
+                        //完成：推进所有在origin处等待该非终结符的项目
+                        completed.insert((item.left, item.origin, j));
+                        let waiting = sets[item.origin].iter()
+                            .filter(|x| x.next_symbol() == Some(item.left))
+                            .map(|x| x.advance())
+                            .collect::<Vec<_>>();
+                        for advanced in waiting {
+                            add(&mut sets, &mut seen, j, advanced);
+                        }
+                    }
+                }
+                index += 1;
+            }
+        }
+        EarleyChart {
+            sentence,
+            symbols: self.grammar.symbol_table().clone(),
+            sets,
+            completed,
+        }
+    }
+    pub fn parse(&self, sentence: &str, max_trees: usize) -> (EarleyChart, Vec<ParseTree>) {
+        //枚举至多max_trees棵语法树，多于一棵说明该句子是二义的
+        let chart = self.recognize(sentence);
+        let trees = self.trees_of(&chart, self.grammar.start(), 0, chart.sentence.len(), max_trees, &mut HashSet::new());
+        (chart, trees)
+    }
+    fn trees_of(&self, chart: &EarleyChart, v_n: char, begin: usize, end: usize, max_trees: usize, visiting: &mut HashSet<(char, usize, usize)>) -> Vec<ParseTree> {
+        //visiting用于跳过A=>+A这样的回路推导，避免产生无穷多棵树
+        if !chart.completed.contains(&(v_n, begin, end)) || !visiting.insert((v_n, begin, end)) {
+            return Vec::new();
+        }
+        let mut trees = Vec::new();
+        for body in collect_ordered_vec(chart.sets[end].iter().filter(|x| x.left == v_n && x.origin == begin && x.next_symbol().is_none()).map(|x| x.body.clone())) {
+            for children in self.sequences_of(chart, &body, begin, end, max_trees, visiting) {
+                let children = if children.is_empty() { vec![ParseTree::leaf(EMPTY_SENTENCE_CHAR)] } else { children };
+                trees.push(ParseTree::new(self.grammar.symbol_table().name(v_n), children));
+                if trees.len() >= max_trees {
+                    visiting.remove(&(v_n, begin, end));
+                    return trees;
+                }
+            }
+        }
+        visiting.remove(&(v_n, begin, end));
+        trees
+    }
+    fn sequences_of(&self, chart: &EarleyChart, body: &[char], begin: usize, end: usize, max_trees: usize, visiting: &mut HashSet<(char, usize, usize)>) -> Vec<Vec<ParseTree>> {
+        //将begin到end之间的子串按body中的符号依次划分
+        let (symbol, rest) = match body.split_first() {
+            None => return if begin == end { vec![Vec::new()] } else { Vec::new() },
+            Some(x) => x,
+        };
+        let mut sequences = Vec::new();
+        if !self.productions.contains_key(symbol) {
+            if begin < end && chart.sentence[begin] == *symbol {
+                for tail in self.sequences_of(chart, rest, begin + 1, end, max_trees, visiting) {
+                    sequences.push([vec![ParseTree::leaf(self.grammar.symbol_table().name(*symbol))], tail].concat());
+                }
+            }
+            sequences.truncate(max_trees);
+            return sequences;
+        }
+        for mid in begin..=end {
+            if !chart.completed.contains(&(*symbol, begin, mid)) {
+                continue;
+            }
+            let tails = self.sequences_of(chart, rest, mid, end, max_trees, visiting);
+            if tails.is_empty() {
+                continue;
+            }
+            for head in self.trees_of(chart, *symbol, begin, mid, max_trees, visiting) {
+                for tail in &tails {
+                    sequences.push([vec![head.clone()], tail.clone()].concat());
+                    if sequences.len() >= max_trees {
+                        return sequences;
+                    }
+                }
+            }
+        }
+        sequences
+    }
+}
+
+pub fn test_sentence_using_earley_parser_cli(parser: &EarleyParser) {
+    loop {
+        let mut buffer = String::new();
+        stdin().read_line(&mut buffer).expect("读取时发生错误！");
+        if buffer.is_empty() {
+            break;
+        }
+        //含多字符符号的文法，输入的终结符之间以空格分隔
+        let sentence = match parser.grammar.symbol_table().encode(&buffer) {
+            Ok(sentence) => sentence,
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        };
+        let (chart, trees) = parser.parse(&sentence, EarleyParser::MAX_PARSE_TREES);
+        println!("Earley项目集如下");
+        println!("{chart}");
+        match trees.len() {
+            0 => println!("该字符串是不可接受的"),
+            1 => println!("该字符串是可接受的，语法树如下"),
+            n if n >= EarleyParser::MAX_PARSE_TREES => println!("该字符串是可接受的且是二义的，至少有{n}棵语法树，列出前{n}棵如下"),
+            n => println!("该字符串是可接受的且是二义的，共有{n}棵语法树如下"),
+        }
+        trees.iter().for_each(|x| println!("{x}"));
+    }
+}
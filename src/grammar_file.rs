@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::grammar_symbol::GrammarSymbol;
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::statics::EMPTY_SENTENCE;
+use crate::utils::fresh_non_terminal;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum EbnfToken {
+    Name(String), //未加引号的名字，出现在左部则为非终结符，否则为终结符
+    Quoted(String), //加引号的终结符
+    Define, //::= -> =
+    Alternative,
+    Open(char), //( [ {
+    Close(char),
+    End, //; 规则结束
+    Empty, //$ 或 ε
+}
+
+#[derive(Debug, Clone)]
+enum EbnfItem {
+    Name(String),
+    Terminal(String),
+    Helper(String), //展开{}[]()时生成的辅助非终结符
+}
+
+type EbnfRule = (String, Vec<Vec<EbnfItem>>);
+
+fn tokenize_ebnf(text: &str) -> Result<Vec<(EbnfToken, usize)>, String> {
+    //返回(记号,所在行号)，#至行尾与(* *)之间为注释
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let (mut index, mut line) = (0, 1);
+    let starts_with = |index: usize, pattern: &str| pattern.chars().enumerate().all(|(i, x)| chars.get(index + i) == Some(&x));
+    while index < chars.len() {
+        let ch = chars[index];
+        if ch == '\n' {
+            line += 1;
+            index += 1;
+        } else if ch.is_whitespace() {
+            index += 1;
+        } else if ch == '#' {
+            while index < chars.len() && chars[index] != '\n' {
+                index += 1;
+            }
+        } else if starts_with(index, "(*") {
+            let begin = line;
+            index += 2;
+            while !starts_with(index, "*)") {
+                match chars.get(index) {
+                    Some('\n') => line += 1,
+                    Some(_) => {}
+                    None => return Err(format!("第{begin}行：注释没有结束")),
+                }
+                index += 1;
+            }
+            index += 2;
+        } else if ch == '"' || ch == '\'' {
+            let end = (index + 1..chars.len()).find(|x| chars[*x] == ch || chars[*x] == '\n')
+                .filter(|x| chars[*x] == ch)
+                .ok_or(format!("第{line}行：引号没有闭合"))?;
+            let terminal = chars[index + 1..end].iter().collect::<String>();
+            if terminal.is_empty() {
+                return Err(format!("第{line}行：终结符不能为空，空串请使用{EMPTY_SENTENCE}"));
+            }
+            tokens.push((EbnfToken::Quoted(terminal), line));
+            index = end + 1;
+        } else if let Some(pattern) = ["::=", "->", "="].into_iter().find(|x| starts_with(index, x)) {
+            tokens.push((EbnfToken::Define, line));
+            index += pattern.chars().count();
+        } else if "|([{)]};".contains(ch) {
+            tokens.push((match ch {
+                '|' => EbnfToken::Alternative,
+                '(' | '[' | '{' => EbnfToken::Open(ch),
+                ')' | ']' | '}' => EbnfToken::Close(ch),
+                _ => EbnfToken::End,
+            }, line));
+            index += 1;
+        } else if let Some(end) = (index + 1..chars.len()).take_while(|x| !chars[*x].is_whitespace()).find(|x| chars[*x] == '>').filter(|_| ch == '<') {
+            //BNF的<name>写法
+            tokens.push((EbnfToken::Name(chars[index + 1..end].iter().collect()), line));
+            index = end + 1;
+        } else {
+            let begin = index;
+            while index < chars.len() && !chars[index].is_whitespace() && !"|([{)]};\"#".contains(chars[index])
+                && !["::=", "->", "="].into_iter().any(|x| starts_with(index, x)) {
+                index += 1;
+            }
+            let name = chars[begin..index].iter().collect::<String>();
+            tokens.push((if name == EMPTY_SENTENCE || name == "ε" { EbnfToken::Empty } else { EbnfToken::Name(name) }, line));
+        }
+    }
+    Ok(tokens)
+}
+
+struct EbnfParser {
+    tokens: Vec<(EbnfToken, usize)>,
+    position: usize,
+    rules: Vec<EbnfRule>,
+    helpers: Vec<EbnfRule>, //展开得到的辅助产生式
+    names: HashSet<String>, //已被占用的名字
+}
+
+impl EbnfParser {
+    fn peek(&self, offset: usize) -> Option<&EbnfToken> {
+        self.tokens.get(self.position + offset).map(|(x, _)| x)
+    }
+    fn line(&self) -> usize {
+        self.tokens.get(self.position).or(self.tokens.last()).map(|(_, x)| *x).unwrap_or(1)
+    }
+    fn is_rule_begin(&self) -> bool {
+        matches!((self.peek(0), self.peek(1)), (Some(EbnfToken::Name(_)), Some(EbnfToken::Define)))
+    }
+    fn parse_rules(&mut self) -> Result<(), String> {
+        while self.peek(0).is_some() {
+            if self.peek(0) == Some(&EbnfToken::End) {
+                self.position += 1;
+                continue;
+            }
+            let left = match (self.peek(0), self.peek(1)) {
+                (Some(EbnfToken::Name(left)), Some(EbnfToken::Define)) => left.clone(),
+                _ => return Err(format!("第{}行：规则应以 名字 ::= 开始", self.line())),
+            };
+            self.position += 2;
+            let alternatives = self.parse_alternatives(&left, None)?;
+            self.rules.push((left, alternatives));
+        }
+        Ok(())
+    }
+    fn parse_alternatives(&mut self, left: &str, close: Option<char>) -> Result<Vec<Vec<EbnfItem>>, String> {
+        //顶层的候选式在 ; 、下一条规则或文件结尾处结束，括号内的候选式在对应的右括号处结束
+        let mut alternatives = vec![Vec::new()];
+        loop {
+            let line = self.line();
+            let token = match self.peek(0) {
+                None if close.is_none() => break,
+                None => return Err(format!("第{line}行：缺少{}", close.unwrap())),
+                Some(_) if close.is_none() && self.is_rule_begin() => break,
+                Some(token) => token.clone(),
+            };
+            self.position += 1;
+            let sequence = alternatives.last_mut().unwrap();
+            match token {
+                EbnfToken::Name(name) => sequence.push(EbnfItem::Name(name)),
+                EbnfToken::Quoted(terminal) => sequence.push(EbnfItem::Terminal(terminal)),
+                EbnfToken::Empty => {}
+                EbnfToken::Alternative => alternatives.push(Vec::new()),
+                EbnfToken::End if close.is_none() => break,
+                EbnfToken::Close(ch) if Some(ch) == close => break,
+                EbnfToken::Open(open) => {
+                    let close = match open {
+                        '(' => ')',
+                        '[' => ']',
+                        _ => '}',
+                    };
+                    let inner = self.parse_alternatives(left, Some(close))?;
+                    self.desugar(left, open, inner, &mut alternatives);
+                }
+                EbnfToken::Close(ch) => return Err(format!("第{line}行：多余的{ch}")),
+                _ => return Err(format!("第{line}行：此处不应出现 ; 或 ::=")),
+            }
+        }
+        Ok(alternatives)
+    }
+    fn desugar(&mut self, left: &str, open: char, mut inner: Vec<Vec<EbnfItem>>, alternatives: &mut [Vec<EbnfItem>]) {
+        //(α)只有一个候选式时直接展开，否则引入A->α
+        //[α]引入A->α|$，{α}引入A->αA|$
+        let sequence = alternatives.last_mut().unwrap();
+        if open == '(' && inner.len() == 1 {
+            sequence.append(&mut inner[0]);
+            return;
+        }
+        let suffix = match open {
+            '(' => "grp",
+            '[' => "opt",
+            _ => "rep",
+        };
+        let helper = (1..).map(|x| if x == 1 { format!("{left}_{suffix}") } else { format!("{left}_{suffix}{x}") })
+            .find(|x| !self.names.contains(x))
+            .unwrap();
+        self.names.insert(helper.clone());
+        if open == '{' {
+            inner.iter_mut().for_each(|x| x.push(EbnfItem::Helper(helper.clone())));
+        }
+        if open != '(' {
+            inner.push(Vec::new());
+        }
+        sequence.push(EbnfItem::Helper(helper.clone()));
+        self.helpers.push((helper, inner));
+    }
+}
+
+impl PushDownAutomatonGrammar {
+    pub fn build_with_ebnf(text: &str, start: Option<&str>) -> Result<PushDownAutomatonGrammar, String> {
+        //规则形如 expr ::= term { ("+" | "-") term } ; 未给出开始符号时以第一条规则的左部为开始符号
+        let tokens = tokenize_ebnf(text)?;
+        let names = tokens.iter().filter_map(|(x, _)| match x {
+            EbnfToken::Name(x) | EbnfToken::Quoted(x) => Some(x.clone()),
+            _ => None,
+        }).collect::<HashSet<_>>();
+        let single_char = names.iter().all(|x| x.chars().count() == 1);
+        let mut parser = EbnfParser { tokens, position: 0, rules: Vec::new(), helpers: Vec::new(), names };
+        parser.parse_rules()?;
+        //文法中的符号均为单字符时，辅助非终结符改用未被占用的大写字母，使按字符构建的分析工具也能使用该文法
+        let mut helper_names = HashMap::new();
+        if single_char {
+            let mut used = parser.names.iter().filter(|x| x.chars().count() == 1).filter_map(|x| x.chars().next()).collect::<HashSet<_>>();
+            for (helper, _) in &parser.helpers {
+                let code = fresh_non_terminal(&used).ok_or("辅助非终结符过多，无法命名")?;
+                used.insert(code);
+                helper_names.insert(helper.clone(), code.to_string());
+            }
+        }
+        let rename = |name: String| helper_names.get(&name).cloned().unwrap_or(name);
+        let start = match start {
+            Some(start) => start.to_string(),
+            None => parser.rules.first().map(|(x, _)| x.clone()).ok_or("文法文件中没有任何规则")?,
+        };
+        let left_names = parser.rules.iter().map(|(x, _)| x.clone()).collect::<HashSet<_>>();
+        let rules = parser.rules.into_iter().chain(parser.helpers)
+            .map(|(left, alternatives)| (rename(left), alternatives.into_iter().map(|x| x.into_iter().map(|x| match x {
+                EbnfItem::Name(name) if left_names.contains(&name) => GrammarSymbol::NonTerminal(name),
+                EbnfItem::Name(name) | EbnfItem::Terminal(name) => GrammarSymbol::Terminal(name),
+                EbnfItem::Helper(name) => GrammarSymbol::NonTerminal(rename(name)),
+            }).collect()).collect()))
+            .collect();
+        PushDownAutomatonGrammar::build_with_rules(rules, &start)
+    }
+}
+
+pub fn load_grammar_file(path: &str, start: Option<&str>) -> Result<PushDownAutomatonGrammar, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("读取文法文件{path}时发生错误：{e}"))?;
+    PushDownAutomatonGrammar::build_with_ebnf(&text, start)
+}
@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::prediction_analyzer::PredictionAnalyzer;
+use crate::statics::{EMPTY_SENTENCE, EMPTY_SENTENCE_CHAR};
+use crate::utils::collect_ordered_vec;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum GrammarSymbol {
+    Terminal(String),
+    NonTerminal(String),
+}
+
+impl GrammarSymbol {
+    pub fn name(&self) -> &str {
+        match self {
+            GrammarSymbol::Terminal(name) | GrammarSymbol::NonTerminal(name) => name,
+        }
+    }
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, GrammarSymbol::Terminal(_))
+    }
+}
+
+impl Display for GrammarSymbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SymbolTable {
+    symbols: HashMap<char, GrammarSymbol>, //内部编码到文法符号，文法中的算法均以编码进行计算
+    codes: HashMap<String, char>,
+}
+
+impl SymbolTable {
+    pub fn intern(&mut self, symbol: GrammarSymbol) -> Result<char, String> {
+        //单字符的名字直接以自身作为编码，多字符的名字依次使用私用区的字符作为编码
+        if let Some(code) = self.codes.get(symbol.name()) {
+            return match &self.symbols[code] {
+                x if x.is_terminal() == symbol.is_terminal() => Ok(*code),
+                _ => Err(format!("符号{symbol}既是终结符又是非终结符")),
+            };
+        }
+        let mut chars = symbol.name().chars();
+        let code = match (chars.next(), chars.next()) {
+            (None, _) => return Err("文法符号不能为空".to_string()),
+            (Some(EMPTY_SENTENCE_CHAR | PredictionAnalyzer::BEGIN_END_CHAR), None) => return Err(format!("{symbol} 为保留符号")),
+            (Some(ch), None) => ch,
+            _ => (SymbolTable::PRIVATE_USE_BEGIN..=SymbolTable::PRIVATE_USE_END)
+                .filter_map(char::from_u32)
+                .find(|x| !self.symbols.contains_key(x))
+                .ok_or("文法符号过多")?,
+        };
+        self.codes.insert(symbol.name().to_string(), code);
+        self.symbols.insert(code, symbol);
+        Ok(code)
+    }
+    pub fn code(&self, name: &str) -> Option<char> {
+        self.codes.get(name).copied()
+    }
+    pub fn name(&self, code: char) -> String {
+        self.symbols.get(&code).map(|x| x.to_string()).unwrap_or(code.to_string())
+    }
+    pub fn is_multi_char(&self) -> bool {
+        //含有多字符的名字时，符号串中的符号需要以空格分隔
+        self.symbols.values().any(|x| x.name().chars().count() > 1)
+    }
+    pub fn join<T: IntoIterator<Item=char>>(&self, codes: T) -> String {
+        //依次连接符号的名字，空序列得到空串
+        codes.into_iter().map(|x| self.name(x)).collect::<Vec<_>>().join(if self.is_multi_char() { " " } else { "" })
+    }
+    pub fn show_sequence<T: IntoIterator<Item=char>>(&self, codes: T) -> String {
+        match self.join(codes) {
+            x if x.is_empty() => EMPTY_SENTENCE.to_string(),
+            x => x,
+        }
+    }
+    pub fn encode(&self, sentence: &str) -> Result<String, String> {
+        //将输入的句子转换为编码串：含多字符符号时以空白分隔，否则逐字符读入，单独的$表示空串
+        let sentence = sentence.trim();
+        if sentence == EMPTY_SENTENCE {
+            Ok(String::new())
+        } else if self.is_multi_char() {
+            sentence.split_whitespace()
+                .map(|x| self.code(x).filter(|x| self.symbols[x].is_terminal()).ok_or(format!("{x} 不是该文法的终结符")))
+                .collect()
+        } else {
+            Ok(sentence.to_string())
+        }
+    }
+    pub fn show_production(&self, production: &str) -> String {
+        if production == EMPTY_SENTENCE {
+            EMPTY_SENTENCE.to_string()
+        } else {
+            self.show_sequence(production.chars())
+        }
+    }
+    pub fn show_set(&self, set: &HashSet<char>) -> String {
+        format!("{{{}}}", collect_ordered_vec(set.iter().map(|x| self.name(*x))).join(", "))
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::statics::{GRAMMAR_SPLIT_IO_UNIT, GRAMMAR_SPLIT_TARGET_UNIT};
+use crate::utils::{collect_ordered_vec, fresh_non_terminal, join_production, split_production};
+
+pub type SymbolProductions = HashMap<char, Vec<Vec<char>>>;
+//以符号序列表示的产生式集合，便于对产生式进行改写
+
+impl PushDownAutomatonGrammar {
+    pub fn symbol_productions(&self) -> SymbolProductions {
+        self.production_set().iter()
+            .map(|(left_v_n, production_set)| (*left_v_n, collect_ordered_vec(production_set.iter().map(|x| split_production(x)))))
+            .collect()
+    }
+    pub fn rebuild_with_productions(&self, productions: SymbolProductions, start: char) -> PushDownAutomatonGrammar {
+        //根据改写后的产生式重新计算终结符与非终结符集合
+        let mut non_terminal = productions.keys().copied().collect::<HashSet<_>>();
+        non_terminal.insert(start);
+        for body in productions.values().flatten() {
+            body.iter().filter(|x| self.non_terminal().contains(x)).for_each(|x| { non_terminal.insert(*x); });
+        }
+        let terminal = productions.values().flatten().flatten().filter(|x| !non_terminal.contains(x)).copied().collect();
+        let production_set = productions.into_iter()
+            .filter(|(_, bodies)| !bodies.is_empty())
+            .map(|(left_v_n, bodies)| (left_v_n, bodies.iter().map(|x| join_production(x)).collect::<HashSet<_>>()))
+            .collect();
+        PushDownAutomatonGrammar::new(terminal, non_terminal, production_set, start).with_symbol_table(self.symbol_table().clone())
+    }
+    pub fn ordered_non_terminal(&self) -> Vec<char> {
+        //从开始符号出发按广度优先的顺序排列非终结符，不可达的非终结符按字符顺序排在最后
+        let productions = self.symbol_productions();
+        let mut order = vec![self.start()];
+        let mut index = 0;
+        while index < order.len() {
+            for body in productions.get(&order[index]).into_iter().flatten() {
+                for symbol in body {
+                    if self.non_terminal().contains(symbol) && !order.contains(symbol) {
+                        order.push(*symbol);
+                    }
+                }
+            }
+            index += 1;
+        }
+        let rest = collect_ordered_vec(self.non_terminal().iter().copied().filter(|x| !order.contains(x)));
+        order.extend(rest);
+        order
+    }
+    fn used_symbols(&self) -> HashSet<char> {
+        self.terminal().union(self.non_terminal()).copied().collect()
+    }
+    pub fn has_left_recursion(&self) -> bool {
+        //以产生式右部的首符号建立有向图 A->B，首符号可推出空串时其后的符号也是首符号，图中存在环即含有左递归
+        fn dfs(now: char, productions: &SymbolProductions, nullable: &HashSet<char>, visiting: &mut HashSet<char>, finished: &mut HashSet<char>) -> bool {
+            if finished.contains(&now) {
+                return false;
+            }
+            if !visiting.insert(now) {
+                return true;
+            }
+            for body in productions.get(&now).into_iter().flatten() {
+                for symbol in body {
+                    if productions.contains_key(symbol) && dfs(*symbol, productions, nullable, visiting, finished) {
+                        return true;
+                    }
+                    if !nullable.contains(symbol) {
+                        break;
+                    }
+                }
+            }
+            visiting.remove(&now);
+            finished.insert(now);
+            false
+        }
+        let productions = self.symbol_productions();
+        let nullable = self.get_nullable_set();
+        let mut finished = HashSet::new();
+        self.ordered_non_terminal().into_iter().any(|v_n| dfs(v_n, &productions, &nullable, &mut HashSet::new(), &mut finished))
+    }
+    fn substitute_leading(productions: &mut SymbolProductions, target: char, leading: char) {
+        //将target的候选式中以leading开头的 leading γ 替换为 δ γ，δ为leading的所有候选式
+        let leading_bodies = productions.get(&leading).cloned().unwrap_or_default();
+        let mut replaced = Vec::new();
+        for body in productions.remove(&target).unwrap_or_default() {
+            if body.first() == Some(&leading) {
+                for delta in &leading_bodies {
+                    let new_body = [delta.as_slice(), &body[1..]].concat();
+                    if !replaced.contains(&new_body) {
+                        replaced.push(new_body);
+                    }
+                }
+            } else if !replaced.contains(&body) {
+                replaced.push(body);
+            }
+        }
+        productions.insert(target, replaced);
+    }
+    pub fn eliminate_left_recursion(&self) -> Result<PushDownAutomatonGrammar, String> {
+        //左递归隐藏在可推出空串的前缀之后（如A->BA|a,B->$）时，先消除空产生式并化简，再重新改写
+        let grammar = self.rewrite_left_recursion()?;
+        if !grammar.has_left_recursion() {
+            return Ok(grammar);
+        }
+        if !self.get_nullable_set().is_empty() {
+            let (reduced, _) = self.eliminate_empty_production()?.reduce();
+            let grammar = reduced.rewrite_left_recursion()?;
+            if !grammar.has_left_recursion() {
+                return Ok(grammar);
+            }
+        }
+        Err("文法含有回路，无法完全消除左递归".to_string())
+    }
+    fn rewrite_left_recursion(&self) -> Result<PushDownAutomatonGrammar, String> {
+        //将非终结符排序为A1...An，对每个Ai先将Ai->Ajγ(j<i)中的Aj代入，再消除Ai的直接左递归
+        //Ai->Aiα|β 改写为 Ai->βAi' Ai'->αAi'|$
+        //要求文法不含A=>+A的回路且左部不含可推出空串的前缀
+        let order = self.ordered_non_terminal();
+        let mut used = self.used_symbols();
+        let mut productions = self.symbol_productions();
+        for (i, &a_i) in order.iter().enumerate() {
+            for &a_j in order.iter().take(i) {
+                PushDownAutomatonGrammar::substitute_leading(&mut productions, a_i, a_j);
+            }
+            let (recursive, others): (Vec<_>, Vec<_>) = productions.remove(&a_i).unwrap_or_default()
+                .into_iter().partition(|body| body.first() == Some(&a_i));
+            if recursive.is_empty() {
+                productions.insert(a_i, others);
+                continue;
+            }
+            let new_v_n = fresh_non_terminal(&used).ok_or(format!("消除{}的左递归时无法生成新的非终结符", self.symbol_table().name(a_i)))?;
+            used.insert(new_v_n);
+            productions.insert(a_i, others.into_iter().map(|beta| [beta, vec![new_v_n]].concat()).collect());
+            let mut new_bodies = recursive.into_iter()
+                .filter(|body| body.len() > 1)//A->A 这样的产生式无意义，直接删除
+                .map(|body| [&body[1..], &[new_v_n]].concat())
+                .collect::<Vec<_>>();
+            new_bodies.push(Vec::new());
+            productions.insert(new_v_n, new_bodies);
+        }
+        Ok(self.rebuild_with_productions(productions, self.start()))
+    }
+    fn find_common_first(bodies: &[Vec<char>]) -> Option<char> {
+        //找到被至少两个候选式共享的首符号
+        collect_ordered_vec(bodies.iter().filter_map(|x| x.first().copied()))
+            .windows(2).find(|pair| pair[0] == pair[1]).map(|pair| pair[0])
+    }
+    pub fn has_common_prefix(&self) -> bool {
+        self.symbol_productions().values().any(|bodies| PushDownAutomatonGrammar::find_common_first(bodies).is_some())
+    }
+    pub fn left_factor(&self) -> Result<(PushDownAutomatonGrammar, Vec<String>), String> {
+        //反复提取候选式的最长公共左因子 A->αβ1|αβ2|γ 改写为 A->αA'|γ A'->β1|β2
+        let symbols = self.symbol_table();
+        let show = |bodies: &[Vec<char>]| bodies.iter().map(|x| symbols.show_sequence(x.iter().copied())).collect::<Vec<_>>().join(GRAMMAR_SPLIT_TARGET_UNIT);
+        let mut used = self.used_symbols();
+        let mut productions = self.symbol_productions();
+        let mut report = Vec::new();
+        let mut queue = VecDeque::from(self.ordered_non_terminal());
+        while let Some(v_n) = queue.pop_front() {
+            while let Some(first) = productions.get(&v_n).and_then(|bodies| PushDownAutomatonGrammar::find_common_first(bodies)) {
+                let bodies = productions.remove(&v_n).unwrap_or_default();
+                let (group, mut others): (Vec<_>, Vec<_>) = bodies.iter().cloned().partition(|x| x.first() == Some(&first));
+                let mut prefix_len = 1;
+                while group.iter().all(|x| x.len() > prefix_len && x[prefix_len] == group[0][prefix_len]) {
+                    prefix_len += 1;
+                }
+                let prefix = group[0][..prefix_len].to_vec();
+                let new_v_n = fresh_non_terminal(&used).ok_or(format!("提取{}的左因子时无法生成新的非终结符", symbols.name(v_n)))?;
+                used.insert(new_v_n);
+                let mut rests = Vec::new();
+                for body in &group {
+                    let rest = body[prefix_len..].to_vec();
+                    if !rests.contains(&rest) {
+                        rests.push(rest);
+                    }
+                }
+                others.push([prefix.clone(), vec![new_v_n]].concat());
+                report.push(format!(
+                    "{v_n}{GRAMMAR_SPLIT_IO_UNIT}{} 提取公共左因子{} 得到 {v_n}{GRAMMAR_SPLIT_IO_UNIT}{}, {}{GRAMMAR_SPLIT_IO_UNIT}{}",
+                    show(&bodies), symbols.show_sequence(prefix.iter().copied()), show(&others), symbols.name(new_v_n), show(&rests),
+                    v_n = symbols.name(v_n)
+                ));
+                productions.insert(v_n, others);
+                productions.insert(new_v_n, rests);
+                queue.push_back(new_v_n);
+            }
+        }
+        Ok((self.rebuild_with_productions(productions, self.start()), report))
+    }
+    pub fn get_generating_set(&self) -> HashSet<char> {
+        //迭代计算能推导出终结符串的非终结符：某个候选式中的符号均为终结符或已知可终止的非终结符
+        let productions = self.symbol_productions();
+        let mut generating = HashSet::new();
+        loop {
+            let mut closed = true;
+            for (left_v_n, bodies) in &productions {
+                if generating.contains(left_v_n) {
+                    continue;
+                }
+                if bodies.iter().any(|body| body.iter().all(|x| self.terminal().contains(x) || generating.contains(x))) {
+                    generating.insert(*left_v_n);
+                    closed = false;
+                }
+            }
+            if closed {
+                break;
+            }
+        }
+        generating
+    }
+    pub fn reduce(&self) -> (PushDownAutomatonGrammar, Vec<String>) {
+        //先删除不可终止的符号，再删除从开始符号不可达的符号，顺序不能颠倒
+        let mut report = Vec::new();
+        let symbols = self.symbol_table();
+        let name = |x: char| symbols.name(x);
+        let generating = self.get_generating_set();
+        let mut productions = self.symbol_productions();
+        for v_n in self.ordered_non_terminal() {
+            if generating.contains(&v_n) {
+                continue;
+            }
+            if !productions.contains_key(&v_n) {
+                report.push(format!("删除{}：没有任何产生式", name(v_n)));
+            } else if v_n == self.start() {
+                report.push(format!("删除{}的所有产生式：开始符号无法推导出终结符串，文法的语言为空", name(v_n)));
+            } else {
+                report.push(format!("删除{}：无法推导出终结符串", name(v_n)));
+            }
+            productions.remove(&v_n);
+        }
+        for (left_v_n, bodies) in productions.iter_mut() {
+            let (kept, removed): (Vec<_>, Vec<_>) = bodies.drain(..)
+                .partition(|body| body.iter().all(|x| self.terminal().contains(x) || generating.contains(x)));
+            for body in removed {
+                report.push(format!("删除产生式{}{GRAMMAR_SPLIT_IO_UNIT}{}：含有无法推导出终结符串的符号", name(*left_v_n), symbols.show_sequence(body)));
+            }
+            *bodies = kept;
+        }
+        //从开始符号出发计算可达符号
+        let mut reachable = HashSet::from([self.start()]);
+        let mut stack = vec![self.start()];
+        while let Some(now) = stack.pop() {
+            for symbol in productions.get(&now).into_iter().flatten().flatten() {
+                if reachable.insert(*symbol) && productions.contains_key(symbol) {
+                    stack.push(*symbol);
+                }
+            }
+        }
+        for v_n in collect_ordered_vec(productions.keys().copied().filter(|x| !reachable.contains(x))) {
+            report.push(format!("删除{}：从开始符号{}不可达", name(v_n), name(self.start())));
+            productions.remove(&v_n);
+        }
+        for v_t in collect_ordered_vec(self.terminal().iter().copied().filter(|x| !reachable.contains(x))) {
+            report.push(format!("删除终结符{}：从开始符号{}不可达", name(v_t), name(self.start())));
+        }
+        (self.rebuild_with_productions(productions, self.start()), report)
+    }
+    pub fn get_nullable_set(&self) -> HashSet<char> {
+        //迭代计算可推导出空串的非终结符：某个候选式中的符号均为可空的非终结符（空候选式亦然）
+        let productions = self.symbol_productions();
+        let mut nullable = HashSet::new();
+        loop {
+            let mut closed = true;
+            for (left_v_n, bodies) in &productions {
+                if !nullable.contains(left_v_n) && bodies.iter().any(|body| body.iter().all(|x| nullable.contains(x))) {
+                    nullable.insert(*left_v_n);
+                    closed = false;
+                }
+            }
+            if closed {
+                break;
+            }
+        }
+        nullable
+    }
+    pub fn eliminate_empty_production(&self) -> Result<PushDownAutomatonGrammar, String> {
+        //对每个候选式枚举可空符号出现与否的所有组合，并删除所有空产生式
+        //若开始符号可空，则引入新的开始符号S'->S|$ 保留空串
+        let nullable = self.get_nullable_set();
+        let mut productions: SymbolProductions = HashMap::new();
+        for (left_v_n, bodies) in self.symbol_productions() {
+            let mut new_bodies: Vec<Vec<char>> = Vec::new();
+            for body in bodies {
+                let mut combinations = vec![Vec::new()];
+                for symbol in body {
+                    let with_symbol = combinations.iter().map(|x| [x.as_slice(), &[symbol]].concat()).collect::<Vec<_>>();
+                    if nullable.contains(&symbol) {
+                        combinations.extend(with_symbol);
+                    } else {
+                        combinations = with_symbol;
+                    }
+                }
+                for combination in combinations {
+                    if !combination.is_empty() && !new_bodies.contains(&combination) {
+                        new_bodies.push(combination);
+                    }
+                }
+            }
+            productions.insert(left_v_n, new_bodies);
+        }
+        let mut start = self.start();
+        if nullable.contains(&start) {
+            start = fresh_non_terminal(&self.used_symbols()).ok_or("无法生成新的开始符号")?;
+            productions.insert(start, vec![vec![self.start()], Vec::new()]);
+        }
+        Ok(self.rebuild_with_productions(productions, start))
+    }
+    pub fn eliminate_unit_production(&self) -> PushDownAutomatonGrammar {
+        //计算单产生式闭包 A=>*B，将B的非单产生式加入A
+        let productions = self.symbol_productions();
+        let is_unit = |body: &Vec<char>| body.len() == 1 && self.non_terminal().contains(&body[0]);
+        let mut new_productions: SymbolProductions = HashMap::new();
+        for left_v_n in self.ordered_non_terminal() {
+            let mut unit_closure = vec![left_v_n];
+            let mut index = 0;
+            while index < unit_closure.len() {
+                for body in productions.get(&unit_closure[index]).into_iter().flatten() {
+                    if is_unit(body) && !unit_closure.contains(&body[0]) {
+                        unit_closure.push(body[0]);
+                    }
+                }
+                index += 1;
+            }
+            let mut new_bodies: Vec<Vec<char>> = Vec::new();
+            for body in unit_closure.iter().flat_map(|x| productions.get(x).into_iter().flatten()) {
+                if !is_unit(body) && !new_bodies.contains(body) {
+                    new_bodies.push(body.clone());
+                }
+            }
+            new_productions.insert(left_v_n, new_bodies);
+        }
+        self.rebuild_with_productions(new_productions, self.start())
+    }
+    pub fn is_chomsky_normal_form(&self) -> bool {
+        //A->BC A->a，仅开始符号可以有S->$ 且此时开始符号不出现在右部
+        let productions = self.symbol_productions();
+        let start_on_right = productions.values().flatten().flatten().any(|x| *x == self.start());
+        productions.iter().all(|(left_v_n, bodies)| bodies.iter().all(|body| match body.len() {
+            0 => *left_v_n == self.start() && !start_on_right,
+            1 => self.terminal().contains(&body[0]),
+            2 => body.iter().all(|x| self.non_terminal().contains(x)),
+            _ => false,
+        }))
+    }
+    pub fn to_chomsky_normal_form(&self) -> Result<PushDownAutomatonGrammar, String> {
+        //依次消除空产生式、单产生式和无用符号，再将长度不小于2的候选式中的终结符替换为新的非终结符，最后将长候选式拆分为二元
+        let (cleaned, _) = self.eliminate_empty_production()?.eliminate_unit_production().reduce();
+        let mut used = cleaned.used_symbols();
+        let mut productions = cleaned.symbol_productions();
+        let mut terminal_v_n: HashMap<char, char> = HashMap::new();
+        for v_t in collect_ordered_vec(cleaned.terminal().iter().copied()) {
+            let used_in_long_body = productions.values().flatten().any(|body| body.len() >= 2 && body.contains(&v_t));
+            if used_in_long_body {
+                let new_v_n = fresh_non_terminal(&used).ok_or(format!("为终结符{}生成新的非终结符失败", self.symbol_table().name(v_t)))?;
+                used.insert(new_v_n);
+                terminal_v_n.insert(v_t, new_v_n);
+            }
+        }
+        for body in productions.values_mut().flatten() {
+            if body.len() >= 2 {
+                body.iter_mut().filter(|x| terminal_v_n.contains_key(x)).for_each(|x| *x = terminal_v_n[x]);
+            }
+        }
+        for (v_t, v_n) in &terminal_v_n {
+            productions.insert(*v_n, vec![vec![*v_t]]);
+        }
+        //A->X1X2...Xk 拆分为 A->X1A1 A1->X2A2 ... 相同的后缀共用一个非终结符
+        let mut suffix_v_n: HashMap<Vec<char>, char> = HashMap::new();
+        let mut pending = collect_ordered_vec(productions.keys().copied());
+        while let Some(left_v_n) = pending.pop() {
+            let mut bodies = productions.remove(&left_v_n).unwrap_or_default();
+            for body in bodies.iter_mut().filter(|x| x.len() > 2) {
+                let suffix = body[1..].to_vec();
+                let new_v_n = match suffix_v_n.get(&suffix) {
+                    Some(v_n) => *v_n,
+                    None => {
+                        let new_v_n = fresh_non_terminal(&used).ok_or("拆分候选式时无法生成新的非终结符")?;
+                        used.insert(new_v_n);
+                        suffix_v_n.insert(suffix.clone(), new_v_n);
+                        productions.insert(new_v_n, vec![suffix]);
+                        pending.push(new_v_n);
+                        new_v_n
+                    }
+                };
+                *body = vec![body[0], new_v_n];
+            }
+            productions.insert(left_v_n, bodies);
+        }
+        Ok(cleaned.rebuild_with_productions(productions, cleaned.start()))
+    }
+    pub fn check_greibach_normal_form(&self) -> Result<(), String> {
+        //A->aα，α中均为非终结符，仅开始符号可以有S->$ 且此时开始符号不出现在右部
+        let (productions, symbols) = (self.symbol_productions(), self.symbol_table());
+        let start_on_right = productions.values().flatten().flatten().any(|x| *x == self.start());
+        for left_v_n in self.ordered_non_terminal() {
+            for body in productions.get(&left_v_n).into_iter().flatten() {
+                let shown = format!("{}{GRAMMAR_SPLIT_IO_UNIT}{}", symbols.name(left_v_n), symbols.show_sequence(body.iter().copied()));
+                match body.split_first() {
+                    None if left_v_n != self.start() => return Err(format!("{shown} 非开始符号不能推出空串")),
+                    None if start_on_right => return Err(format!("{shown} 开始符号出现在右部时不能推出空串")),
+                    None => {}
+                    Some((first, _)) if !self.terminal().contains(first) => return Err(format!("{shown} 右部不以终结符开头")),
+                    Some((_, rest)) if rest.iter().any(|x| !self.non_terminal().contains(x)) => return Err(format!("{shown} 首符号之后含有终结符")),
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn to_greibach_normal_form(&self) -> Result<PushDownAutomatonGrammar, String> {
+        //从乔姆斯基范式出发，将非终结符排序为A1...An
+        //1.对i=1...n 将Ai->Ajγ(j<i)代入，并消除直接左递归 Ai->Aiα|β 改写为 Ai->β|βBi Bi->α|αBi
+        //2.此时Ai的候选式以终结符或Aj(j>i)开头，对i=n...1 依次将开头的非终结符代入
+        //3.最后将新引入的Bi开头的非终结符代入
+        let cnf = self.to_chomsky_normal_form()?;
+        let start = cnf.start();
+        let mut productions = cnf.symbol_productions();
+        let keeps_empty = productions.get(&start).is_some_and(|x| x.contains(&Vec::new()));
+        if let Some(bodies) = productions.get_mut(&start) {
+            bodies.retain(|x| !x.is_empty());
+        }
+        let order = cnf.ordered_non_terminal();
+        let mut used = cnf.used_symbols();
+        let mut new_v_ns = Vec::new();
+        for (i, &a_i) in order.iter().enumerate() {
+            for &a_j in order.iter().take(i) {
+                PushDownAutomatonGrammar::substitute_leading(&mut productions, a_i, a_j);
+            }
+            let (recursive, others): (Vec<_>, Vec<_>) = productions.remove(&a_i).unwrap_or_default()
+                .into_iter().partition(|body| body.first() == Some(&a_i));
+            if recursive.is_empty() {
+                productions.insert(a_i, others);
+                continue;
+            }
+            let new_v_n = fresh_non_terminal(&used).ok_or(format!("消除{}的左递归时无法生成新的非终结符", cnf.symbol_table().name(a_i)))?;
+            used.insert(new_v_n);
+            new_v_ns.push(new_v_n);
+            productions.insert(a_i, others.iter().cloned().chain(others.iter().map(|beta| [beta.as_slice(), &[new_v_n]].concat())).collect());
+            let tails = recursive.into_iter().map(|body| body[1..].to_vec()).filter(|x| !x.is_empty()).collect::<Vec<_>>();
+            productions.insert(new_v_n, tails.iter().cloned().chain(tails.iter().map(|alpha| [alpha.as_slice(), &[new_v_n]].concat())).collect());
+        }
+        for &a_i in order.iter().rev().chain(new_v_ns.iter()) {
+            while let Some(leading) = productions.get(&a_i)
+                .and_then(|bodies| bodies.iter().filter_map(|x| x.first()).find(|x| productions.contains_key(x)).copied()) {
+                if leading == a_i {
+                    return Err(format!("{}仍含有左递归，无法转换为格雷巴赫范式", cnf.symbol_table().name(a_i)));
+                }
+                PushDownAutomatonGrammar::substitute_leading(&mut productions, a_i, leading);
+            }
+        }
+        if keeps_empty {
+            productions.entry(start).or_default().push(Vec::new());
+        }
+        let (gnf, _) = cnf.rebuild_with_productions(productions, start).reduce();
+        gnf.check_greibach_normal_form()?;
+        Ok(gnf)
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::statics::SPLIT_UNITS;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Token {
+    kind: String, //记号的种类，对应文法中的终结符名
+    lexeme: String,
+    position: usize, //记号在源程序中的字符下标
+}
+
+impl Token {
+    pub fn new<K: Into<String>, L: Into<String>>(kind: K, lexeme: L, position: usize) -> Self {
+        Token {
+            kind: kind.into(),
+            lexeme: lexeme.into(),
+            position,
+        }
+    }
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Lexer {
+    keywords: Vec<String>, //由字母组成的终结符，如if while
+    operators: Vec<String>, //其余终结符，按长度降序排列以便最长匹配
+    kind_map: HashMap<String, String>, //将id num等种类改名为文法中的终结符
+}
+
+impl Lexer {
+    pub fn new(grammar: &PushDownAutomatonGrammar) -> Self {
+        let names = grammar.terminal().iter().map(|x| grammar.symbol_table().name(*x));
+        let (keywords, mut operators): (Vec<_>, Vec<_>) = names.partition(|x| Lexer::is_identifier(x));
+        operators.sort_by_key(|x| std::cmp::Reverse(x.chars().count()));
+        Lexer {
+            keywords,
+            operators,
+            kind_map: HashMap::new(),
+        }
+    }
+    pub fn with_kind_map(mut self, kind_map: HashMap<String, String>) -> Self {
+        self.kind_map = kind_map;
+        self
+    }
+    pub fn parse_kind_map(text: &str) -> Result<HashMap<String, String>, String> {
+        //形如 id:i,num:i
+        text.split(SPLIT_UNITS)
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| x.split_once(Lexer::KIND_MAP_SPLIT_UNIT)
+                .map(|(kind, terminal)| (kind.trim().to_string(), terminal.trim().to_string()))
+                .ok_or(format!("{x} 应形如 种类{}终结符", Lexer::KIND_MAP_SPLIT_UNIT)))
+            .collect()
+    }
+    fn is_identifier(text: &str) -> bool {
+        let mut chars = text.chars();
+        chars.next().is_some_and(|x| x.is_alphabetic() || x == '_') && chars.all(|x| x.is_alphanumeric() || x == '_')
+    }
+    pub fn tokenize(&self, source: &str) -> Vec<Token> {
+        //跳过空白，标识符与关键字、数字、运算符分别按最长匹配切分，无法识别的字符单独成为一个记号
+        let chars = source.chars().collect::<Vec<_>>();
+        let mut tokens = Vec::new();
+        let mut index = 0;
+        while index < chars.len() {
+            let ch = chars[index];
+            let begin = index;
+            if ch.is_whitespace() {
+                index += 1;
+                continue;
+            }
+            //运算符直接与剩余的字符逐个比较，不复制剩余的源程序
+            let starts_with = |x: &&String| x.chars().count() <= chars.len() - index && x.chars().zip(&chars[index..]).all(|(a, b)| a == *b);
+            let kind = if let Some(operator) = self.operators.iter().find(starts_with) {
+                index += operator.chars().count();
+                operator.clone()
+            } else if ch.is_alphabetic() || ch == '_' {
+                while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                    index += 1;
+                }
+                let word = chars[begin..index].iter().collect::<String>();
+                if self.keywords.contains(&word) { word } else { Lexer::IDENTIFIER_KIND.to_string() }
+            } else if ch.is_ascii_digit() {
+                while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.' && chars.get(index + 1).is_some_and(|x| x.is_ascii_digit())) {
+                    index += 1;
+                }
+                Lexer::NUMBER_KIND.to_string()
+            } else {
+                index += 1;
+                ch.to_string()
+            };
+            let kind = self.kind_map.get(&kind).cloned().unwrap_or(kind);
+            tokens.push(Token::new(kind, chars[begin..index].iter().collect::<String>(), begin));
+        }
+        tokens
+    }
+}
+
+pub fn line_column(source: &str, position: usize) -> (usize, usize) {
+    //将字符下标转换为从1开始的行号与列号
+    let prefix = source.chars().take(position).collect::<String>();
+    let line = prefix.matches('\n').count() + 1;
+    let column = prefix.chars().rev().take_while(|x| *x != '\n').count() + 1;
+    (line, column)
+}
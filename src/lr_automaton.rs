@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::deterministic_finite_automaton::{DeterministicFiniteAutomaton, TransFunc};
+use crate::prediction_analyzer::PredictionAnalyzer;
+use crate::pushdown_automaton_grammar::{FirstSet, PushDownAutomatonGrammar};
+use crate::statics::{EMPTY_SENTENCE_CHAR, GRAMMAR_SPLIT_IO_UNIT};
+use crate::utils::{collect_ordered_vec, fresh_non_terminal, join_production};
+
+pub type LrProduction = (char, Vec<char>);
+//编号的产生式，0号为拓广文法的S'->S
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct LrItem {
+    production: usize, //产生式编号
+    dot: usize, //圆点前的符号个数
+}
+
+impl LrItem {
+    pub fn new(production: usize, dot: usize) -> Self {
+        LrItem {
+            production,
+            dot,
+        }
+    }
+    pub fn production(&self) -> usize {
+        self.production
+    }
+}
+
+pub type LrItemSet = BTreeSet<LrItem>;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Lr1Item {
+    core: LrItem, //LR(0)核心项目
+    lookahead: char, //向前搜索符
+}
+
+impl Lr1Item {
+    pub fn new(core: LrItem, lookahead: char) -> Self {
+        Lr1Item {
+            core,
+            lookahead,
+        }
+    }
+    pub fn core(&self) -> LrItem {
+        self.core
+    }
+    pub fn lookahead(&self) -> char {
+        self.lookahead
+    }
+}
+
+pub type Lr1ItemSet = BTreeSet<Lr1Item>;
+
+type LrCollection<T> = (Vec<BTreeSet<T>>, HashMap<(usize, char), usize>);
+
+fn build_collection<T: Ord + Clone, F: Fn(&BTreeSet<T>, char) -> BTreeSet<T>>(start: BTreeSet<T>, symbols: &[char], goto: F) -> LrCollection<T> {
+    //从初始项目集出发，对每个项目集和每个文法符号求GO，直至不再产生新的项目集
+    let mut states = vec![start];
+    let mut index_of = BTreeMap::from([(states[0].clone(), 0)]);
+    let mut trans = HashMap::new();
+    let mut index = 0;
+    while index < states.len() {
+        for &symbol in symbols {
+            let target = goto(&states[index], symbol);
+            if target.is_empty() {
+                continue;
+            }
+            let target_index = *index_of.entry(target.clone()).or_insert_with(|| {
+                states.push(target);
+                states.len() - 1
+            });
+            trans.insert((index, symbol), target_index);
+        }
+        index += 1;
+    }
+    (states, trans)
+}
+
+#[derive(Debug, Clone)]
+pub struct AugmentedGrammar {
+    grammar: PushDownAutomatonGrammar,
+    productions: Vec<LrProduction>,
+}
+
+impl AugmentedGrammar {
+    pub fn new(grammar: &PushDownAutomatonGrammar) -> Result<Self, String> {
+        //引入新的开始符号S'->S，并将产生式编号
+        grammar.check_single_char_symbols("LR分析")?;
+        let used = grammar.terminal().union(grammar.non_terminal()).copied().collect::<HashSet<_>>();
+        let augmented_start = fresh_non_terminal(&used).ok_or("无法生成拓广文法的开始符号")?;
+        let mut productions = vec![(augmented_start, vec![grammar.start()])];
+        let symbol_productions = grammar.symbol_productions();
+        for left_v_n in grammar.ordered_non_terminal() {
+            for body in symbol_productions.get(&left_v_n).into_iter().flatten() {
+                productions.push((left_v_n, body.clone()));
+            }
+        }
+        Ok(AugmentedGrammar {
+            grammar: grammar.clone(),
+            productions,
+        })
+    }
+    pub fn origin_grammar(&self) -> &PushDownAutomatonGrammar {
+        &self.grammar
+    }
+    pub fn production(&self, index: usize) -> &LrProduction {
+        &self.productions[index]
+    }
+    pub fn symbols(&self) -> Vec<char> {
+        //终结符在前，非终结符在后
+        let mut symbols = collect_ordered_vec(self.grammar.terminal().iter().copied());
+        symbols.extend(self.grammar.ordered_non_terminal());
+        symbols
+    }
+    pub fn next_symbol(&self, item: &LrItem) -> Option<char> {
+        self.productions[item.production].1.get(item.dot).copied()
+    }
+    pub fn show_production(&self, index: usize) -> String {
+        let (left, body) = &self.productions[index];
+        format!("{left}{GRAMMAR_SPLIT_IO_UNIT}{}", join_production(body))
+    }
+    pub fn show_item(&self, item: &LrItem) -> String {
+        let (left, body) = &self.productions[item.production];
+        let before = body[..item.dot].iter().collect::<String>();
+        let after = body[item.dot..].iter().collect::<String>();
+        format!("{left}{GRAMMAR_SPLIT_IO_UNIT}{before}·{after}")
+    }
+    pub fn closure(&self, items: LrItemSet) -> LrItemSet {
+        //圆点后为非终结符B时，将B的所有产生式的初始项目加入
+        let mut closure = items;
+        let mut stack = closure.iter().copied().collect::<Vec<_>>();
+        while let Some(item) = stack.pop() {
+            if let Some(symbol) = self.next_symbol(&item) {
+                for (index, (left, _)) in self.productions.iter().enumerate() {
+                    if *left == symbol && closure.insert(LrItem::new(index, 0)) {
+                        stack.push(LrItem::new(index, 0));
+                    }
+                }
+            }
+        }
+        closure
+    }
+    pub fn goto(&self, items: &LrItemSet, symbol: char) -> LrItemSet {
+        let kernel = items.iter()
+            .filter(|x| self.next_symbol(x) == Some(symbol))
+            .map(|x| LrItem::new(x.production, x.dot + 1))
+            .collect();
+        self.closure(kernel)
+    }
+    pub fn show_lr1_item(&self, item: &Lr1Item) -> String {
+        format!("[{}, {}]", self.show_item(&item.core), item.lookahead)
+    }
+    pub fn closure_lr1(&self, items: Lr1ItemSet, first_set: &FirstSet) -> Lr1ItemSet {
+        //对[A->α·Bβ,a]，将B的所有产生式的初始项目以FIRST(βa)中的每个终结符为向前搜索符加入
+        let mut closure = items;
+        let mut stack = closure.iter().copied().collect::<Vec<_>>();
+        while let Some(item) = stack.pop() {
+            let symbol = match self.next_symbol(&item.core) {
+                Some(symbol) => symbol,
+                None => continue,
+            };
+            let beta = &self.productions[item.core.production].1[item.core.dot + 1..];
+            let mut lookaheads = self.grammar.first_of_sequence(beta, first_set);
+            if lookaheads.remove(&EMPTY_SENTENCE_CHAR) {
+                lookaheads.insert(item.lookahead);
+            }
+            for (index, (left, _)) in self.productions.iter().enumerate() {
+                if *left != symbol {
+                    continue;
+                }
+                for lookahead in &lookaheads {
+                    let new_item = Lr1Item::new(LrItem::new(index, 0), *lookahead);
+                    if closure.insert(new_item) {
+                        stack.push(new_item);
+                    }
+                }
+            }
+        }
+        closure
+    }
+    pub fn goto_lr1(&self, items: &Lr1ItemSet, symbol: char, first_set: &FirstSet) -> Lr1ItemSet {
+        let kernel = items.iter()
+            .filter(|x| self.next_symbol(&x.core) == Some(symbol))
+            .map(|x| Lr1Item::new(LrItem::new(x.core.production, x.core.dot + 1), x.lookahead))
+            .collect();
+        self.closure_lr1(kernel, first_set)
+    }
+}
+
+impl Display for AugmentedGrammar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for index in 0..self.productions.len() {
+            writeln!(f, "({index}) {}", self.show_production(index))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LrAutomaton {
+    grammar: AugmentedGrammar,
+    states: Vec<LrItemSet>, //项目集规范族，I0为初始项目集
+    goto: HashMap<(usize, char), usize>, //GO(Ii,X)=Ij
+}
+
+impl LrAutomaton {
+    pub fn build_lr0(grammar: &PushDownAutomatonGrammar) -> Result<Self, String> {
+        //I0=CLOSURE({S'->·S})
+        let grammar = AugmentedGrammar::new(grammar)?;
+        let start = grammar.closure(LrItemSet::from([LrItem::new(0, 0)]));
+        let (states, goto) = build_collection(start, &grammar.symbols(), |items, symbol| grammar.goto(items, symbol));
+        Ok(LrAutomaton {
+            grammar,
+            states,
+            goto,
+        })
+    }
+    pub fn grammar(&self) -> &AugmentedGrammar {
+        &self.grammar
+    }
+    pub fn states(&self) -> &Vec<LrItemSet> {
+        &self.states
+    }
+    pub fn goto(&self) -> &HashMap<(usize, char), usize> {
+        &self.goto
+    }
+    pub fn is_complete(&self, item: &LrItem) -> bool {
+        self.grammar.next_symbol(item).is_none()
+    }
+    pub fn to_dfa(&self) -> Result<(DeterministicFiniteAutomaton, Vec<char>), String> {
+        //识别活前缀的DFA，含归约项目的项目集作为终态（识别出句柄）
+        //返回的Vec为每个项目集对应的DFA状态
+        let mut used = HashSet::new();
+        let mut names = Vec::new();
+        for _ in &self.states {
+            let name = fresh_non_terminal(&used).ok_or("项目集过多，无法命名")?;
+            used.insert(name);
+            names.push(name);
+        }
+        let end_state_set = self.states.iter().enumerate()
+            .filter(|(_, items)| items.iter().any(|x| self.is_complete(x)))
+            .map(|(index, _)| names[index])
+            .collect();
+        let trans = self.goto.iter().map(|((from, symbol), to)| (TransFunc::new(names[*from], *symbol), names[*to])).collect();
+        let dfa = DeterministicFiniteAutomaton::build(
+            self.grammar.symbols().into_iter().collect(),
+            names.iter().copied().collect(),
+            names[0],
+            end_state_set,
+            trans,
+        ).map_err(|_| "构造活前缀DFA失败".to_string())?;
+        Ok((dfa, names))
+    }
+}
+
+impl Display for LrAutomaton {
+    //格式与DFA的输出保持一致
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\n    This is a LR automaton\n    item sets :")?;
+        for (index, items) in self.states.iter().enumerate() {
+            writeln!(f, "    I{index}:")?;
+            //先输出核心项目，再输出闭包中新增的项目
+            let (kernel, others): (Vec<_>, Vec<_>) = items.iter().partition(|x| x.dot > 0 || x.production == 0);
+            for item in kernel.into_iter().chain(others) {
+                writeln!(f, "        {}", self.grammar.show_item(item))?;
+            }
+        }
+        writeln!(f, "    trans :")?;
+        let symbols = self.grammar.symbols();
+        for index in 0..self.states.len() {
+            for symbol in &symbols {
+                if let Some(target) = self.goto.get(&(index, *symbol)) {
+                    writeln!(f, "    I{index}+{symbol}->I{target}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lr1Automaton {
+    grammar: AugmentedGrammar,
+    states: Vec<Lr1ItemSet>, //LR(1)项目集规范族
+    goto: HashMap<(usize, char), usize>,
+}
+
+impl Lr1Automaton {
+    pub fn build_lr1(grammar: &PushDownAutomatonGrammar) -> Result<Self, String> {
+        //I0=CLOSURE({[S'->·S,#]})
+        let (first_set, _) = grammar.get_first_and_follow_set()?;
+        let grammar = AugmentedGrammar::new(grammar)?;
+        let start = grammar.closure_lr1(Lr1ItemSet::from([Lr1Item::new(LrItem::new(0, 0), PredictionAnalyzer::BEGIN_END_CHAR)]), &first_set);
+        let (states, goto) = build_collection(start, &grammar.symbols(), |items, symbol| grammar.goto_lr1(items, symbol, &first_set));
+        Ok(Lr1Automaton {
+            grammar,
+            states,
+            goto,
+        })
+    }
+    pub fn merge_cores(&self) -> (Self, Vec<usize>) {
+        //合并同心项目集（LR(0)核心相同的项目集），返回合并后的自动机及原项目集到合并后项目集的映射
+        let mut index_of: HashMap<LrItemSet, usize> = HashMap::new();
+        let mut states: Vec<Lr1ItemSet> = Vec::new();
+        let mut merged_into = Vec::new();
+        for items in &self.states {
+            let core = items.iter().map(|x| x.core).collect::<LrItemSet>();
+            let index = *index_of.entry(core).or_insert_with(|| {
+                states.push(Lr1ItemSet::new());
+                states.len() - 1
+            });
+            states[index].extend(items.iter().copied());
+            merged_into.push(index);
+        }
+        let goto = self.goto.iter().map(|((from, symbol), to)| ((merged_into[*from], *symbol), merged_into[*to])).collect();
+        (Lr1Automaton {
+            grammar: self.grammar.clone(),
+            states,
+            goto,
+        }, merged_into)
+    }
+    pub fn grammar(&self) -> &AugmentedGrammar {
+        &self.grammar
+    }
+    pub fn states(&self) -> &Vec<Lr1ItemSet> {
+        &self.states
+    }
+    pub fn goto(&self) -> &HashMap<(usize, char), usize> {
+        &self.goto
+    }
+}
+
+impl Display for Lr1Automaton {
+    //同一核心项目的向前搜索符合并输出
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\n    This is a LR(1) automaton\n    item sets :")?;
+        for (index, items) in self.states.iter().enumerate() {
+            writeln!(f, "    I{index}:")?;
+            let mut lookaheads: BTreeMap<LrItem, Vec<char>> = BTreeMap::new();
+            items.iter().for_each(|x| lookaheads.entry(x.core).or_default().push(x.lookahead));
+            let (kernel, others): (Vec<_>, Vec<_>) = lookaheads.iter().partition(|(x, _)| x.dot > 0 || x.production == 0);
+            for (core, lookahead) in kernel.into_iter().chain(others) {
+                let lookahead = lookahead.iter().map(|x| x.to_string()).collect::<Vec<_>>().join("/");
+                writeln!(f, "        [{}, {lookahead}]", self.grammar.show_item(core))?;
+            }
+        }
+        writeln!(f, "    trans :")?;
+        let symbols = self.grammar.symbols();
+        for index in 0..self.states.len() {
+            for symbol in &symbols {
+                if let Some(target) = self.goto.get(&(index, *symbol)) {
+                    writeln!(f, "    I{index}+{symbol}->I{target}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::io::stdin;
+
+use crate::lr_automaton::{AugmentedGrammar, Lr1Automaton, LrAutomaton};
+use crate::prediction_analyzer::PredictionAnalyzer;
+use crate::pushdown_automaton_grammar::{build_push_down_automaton_grammar_with_args, PushDownAutomatonGrammar};
+use crate::r#type::StringArgs;
+use crate::statics::EMPTY_SENTENCE;
+use crate::utils::collect_ordered_vec;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum LrAction {
+    Shift(usize), //移进并转入状态
+    Reduce(usize), //按编号的产生式归约
+    Accept,
+}
+
+impl Display for LrAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LrAction::Shift(state) => write!(f, "s{state}"),
+            LrAction::Reduce(production) => write!(f, "r{production}"),
+            LrAction::Accept => write!(f, "acc"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LrConflict {
+    state: usize,
+    symbol: char,
+    actions: Vec<LrAction>, //冲突的全部动作，表中保留第一个
+    items: Vec<String>, //引起冲突的项目
+    introduced_by_merge: bool, //LALR(1)中合并同心项目集后才出现的冲突
+}
+
+impl LrConflict {
+    pub fn is_shift_reduce(&self) -> bool {
+        self.actions.iter().any(|x| matches!(x, LrAction::Shift(_)))
+    }
+    pub fn is_introduced_by_merge(&self) -> bool {
+        self.introduced_by_merge
+    }
+}
+
+impl Display for LrConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.is_shift_reduce() { "移进-归约冲突" } else { "归约-归约冲突" };
+        let actions = self.actions.iter().map(|x| x.to_string()).collect::<Vec<_>>().join("/");
+        let origin = if self.introduced_by_merge { "（由合并同心项目集引入）" } else { "" };
+        writeln!(f, "ACTION[{},{}]存在{kind}{origin}: {actions}", self.state, self.symbol)?;
+        for item in &self.items {
+            writeln!(f, "    {item}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssoc,
+}
+
+impl Display for Associativity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Associativity::Left => write!(f, "左结合"),
+            Associativity::Right => write!(f, "右结合"),
+            Associativity::NonAssoc => write!(f, "无结合"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PrecedenceDeclarations {
+    levels: HashMap<char, (usize, Associativity)>, //终结符的优先级（越大越优先）及结合性
+}
+
+impl PrecedenceDeclarations {
+    pub fn parse(declarations: &str) -> Result<Self, String> {
+        //格式同yacc，按优先级从低到高排列，以;或换行分隔，如 left:+-;left:*/;right:^;nonassoc:=，因此,也可以声明优先级
+        let mut result = PrecedenceDeclarations::default();
+        for (level, declaration) in declarations.split(PrecedenceDeclarations::DECLARATION_SPLIT_UNITS).map(|x| x.trim()).filter(|x| !x.is_empty()).enumerate() {
+            let (associativity, operators) = declaration.split_once(PrecedenceDeclarations::ASSOCIATIVITY_SPLIT_UNIT)
+                .ok_or(format!("{declaration} 缺少结合性声明"))?;
+            let associativity = match associativity {
+                "left" => Associativity::Left,
+                "right" => Associativity::Right,
+                "nonassoc" => Associativity::NonAssoc,
+                _ => return Err(format!("未知的结合性 {associativity}")),
+            };
+            for operator in operators.chars().filter(|x| !x.is_whitespace()) {
+                if result.levels.insert(operator, (level + 1, associativity)).is_some() {
+                    return Err(format!("{operator} 的优先级被重复声明"));
+                }
+            }
+        }
+        Ok(result)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+    fn production_level(&self, body: &[char]) -> Option<(usize, Associativity)> {
+        //产生式的优先级取右部最右一个声明过优先级的终结符
+        body.iter().rev().find_map(|x| self.levels.get(x)).copied()
+    }
+}
+
+type ActionCandidates = BTreeMap<(usize, char), Vec<(LrAction, String)>>;
+//每个表项的候选动作及产生该动作的项目
+
+#[derive(Debug, Clone)]
+pub struct LrParsingTable {
+    grammar: AugmentedGrammar,
+    state_count: usize,
+    action: HashMap<(usize, char), LrAction>,
+    goto: HashMap<(usize, char), usize>,
+    conflicts: Vec<LrConflict>,
+}
+
+impl LrParsingTable {
+    pub fn build_slr1(grammar: &PushDownAutomatonGrammar) -> Result<(LrAutomaton, Self), String> {
+        //归约项目A->α·只在FOLLOW(A)中的符号上归约
+        let automaton = LrAutomaton::build_lr0(grammar)?;
+        let (_, follow_set) = grammar.get_first_and_follow_set()?;
+        let augmented = automaton.grammar();
+        let mut candidates: ActionCandidates = BTreeMap::new();
+        for (index, items) in automaton.states().iter().enumerate() {
+            for item in items {
+                match augmented.next_symbol(item) {
+                    Some(symbol) if grammar.terminal().contains(&symbol) => {
+                        let target = automaton.goto()[&(index, symbol)];
+                        candidates.entry((index, symbol)).or_default().push((LrAction::Shift(target), augmented.show_item(item)));
+                    }
+                    Some(_) => {}
+                    None if item.production() == 0 => {
+                        candidates.entry((index, PredictionAnalyzer::BEGIN_END_CHAR)).or_default().push((LrAction::Accept, augmented.show_item(item)));
+                    }
+                    None => {
+                        let left = augmented.production(item.production()).0;
+                        for v_t in follow_set.get(&left).into_iter().flatten() {
+                            candidates.entry((index, *v_t)).or_default().push((LrAction::Reduce(item.production()), augmented.show_item(item)));
+                        }
+                    }
+                }
+            }
+        }
+        let goto = automaton.goto().iter()
+            .filter(|((_, symbol), _)| grammar.non_terminal().contains(symbol))
+            .map(|(key, target)| (*key, *target))
+            .collect();
+        let table = LrParsingTable::from_candidates(augmented.clone(), automaton.states().len(), candidates, goto);
+        Ok((automaton, table))
+    }
+    pub fn build_lr1(grammar: &PushDownAutomatonGrammar) -> Result<(Lr1Automaton, Self), String> {
+        //归约项目[A->α·,a]只在向前搜索符a上归约
+        let automaton = Lr1Automaton::build_lr1(grammar)?;
+        let table = LrParsingTable::from_lr1_automaton(&automaton);
+        Ok((automaton, table))
+    }
+    pub fn build_lalr1(grammar: &PushDownAutomatonGrammar) -> Result<(Lr1Automaton, Self), String> {
+        //合并LR(1)的同心项目集，若冲突在被合并的任一LR(1)项目集中都不存在，则是由合并引入的
+        let canonical = Lr1Automaton::build_lr1(grammar)?;
+        let canonical_table = LrParsingTable::from_lr1_automaton(&canonical);
+        let (automaton, merged_into) = canonical.merge_cores();
+        let mut table = LrParsingTable::from_lr1_automaton(&automaton);
+        for conflict in &mut table.conflicts {
+            conflict.introduced_by_merge = !canonical_table.conflicts.iter()
+                .any(|x| merged_into[x.state] == conflict.state && x.symbol == conflict.symbol);
+        }
+        Ok((automaton, table))
+    }
+    fn from_lr1_automaton(automaton: &Lr1Automaton) -> Self {
+        let augmented = automaton.grammar();
+        let origin = augmented.origin_grammar();
+        let mut candidates: ActionCandidates = BTreeMap::new();
+        for (index, items) in automaton.states().iter().enumerate() {
+            for item in items {
+                let core = item.core();
+                match augmented.next_symbol(&core) {
+                    Some(symbol) if origin.terminal().contains(&symbol) => {
+                        let target = automaton.goto()[&(index, symbol)];
+                        candidates.entry((index, symbol)).or_default().push((LrAction::Shift(target), augmented.show_lr1_item(item)));
+                    }
+                    Some(_) => {}
+                    None if core.production() == 0 => {
+                        candidates.entry((index, item.lookahead())).or_default().push((LrAction::Accept, augmented.show_lr1_item(item)));
+                    }
+                    None => {
+                        candidates.entry((index, item.lookahead())).or_default().push((LrAction::Reduce(core.production()), augmented.show_lr1_item(item)));
+                    }
+                }
+            }
+        }
+        let goto = automaton.goto().iter()
+            .filter(|((_, symbol), _)| origin.non_terminal().contains(symbol))
+            .map(|(key, target)| (*key, *target))
+            .collect();
+        LrParsingTable::from_candidates(augmented.clone(), automaton.states().len(), candidates, goto)
+    }
+    fn from_candidates(grammar: AugmentedGrammar, state_count: usize, candidates: ActionCandidates, goto: HashMap<(usize, char), usize>) -> Self {
+        //同一格有多个不同动作时记为冲突，表中优先保留移进，其次保留编号较小的产生式
+        let mut action = HashMap::new();
+        let mut conflicts = Vec::new();
+        for ((state, symbol), candidate) in candidates {
+            let actions = candidate.iter().map(|(x, _)| *x).collect::<BTreeSet<_>>().into_iter().collect::<Vec<_>>();
+            if actions.len() > 1 {
+                conflicts.push(LrConflict {
+                    state,
+                    symbol,
+                    actions: actions.clone(),
+                    items: candidate.into_iter().map(|(_, x)| x).collect::<BTreeSet<_>>().into_iter().collect(),
+                    introduced_by_merge: false,
+                });
+            }
+            action.insert((state, symbol), actions[0]);
+        }
+        LrParsingTable {
+            grammar,
+            state_count,
+            action,
+            goto,
+            conflicts,
+        }
+    }
+    pub fn grammar(&self) -> &AugmentedGrammar {
+        &self.grammar
+    }
+    pub fn conflicts(&self) -> &Vec<LrConflict> {
+        &self.conflicts
+    }
+    pub fn resolve_conflicts(&mut self, declarations: &PrecedenceDeclarations) -> (Vec<String>, Vec<String>) {
+        //仅处理一个移进与一个归约之间的冲突，比较产生式与向前看终结符的优先级，同级时按结合性决定
+        //返回已解决冲突的处理说明与无法解决的原因，无法解决的冲突仍保留在conflicts中
+        let mut resolved = Vec::new();
+        let mut unresolved = Vec::new();
+        let mut remained = Vec::new();
+        for conflict in std::mem::take(&mut self.conflicts) {
+            let cell = format!("ACTION[{},{}]", conflict.state, conflict.symbol);
+            let actions = conflict.actions.iter().map(|x| x.to_string()).collect::<Vec<_>>().join("/");
+            let (shift, production) = match conflict.actions.as_slice() {
+                [LrAction::Shift(target), LrAction::Reduce(production)] => (LrAction::Shift(*target), *production),
+                _ => {
+                    let reason = if conflict.is_shift_reduce() { "同时存在多个归约" } else { "为归约-归约冲突" };
+                    unresolved.push(format!("{cell}的冲突{actions}{reason}，优先级只能解决一个移进与一个归约之间的冲突"));
+                    remained.push(conflict);
+                    continue;
+                }
+            };
+            let reduce = LrAction::Reduce(production);
+            let levels = (declarations.levels.get(&conflict.symbol), declarations.production_level(&self.grammar.production(production).1));
+            let ((symbol_level, associativity), (production_level, _)) = match levels {
+                (Some(symbol_level), Some(production_level)) => (*symbol_level, production_level),
+                (None, _) => {
+                    unresolved.push(format!("{cell}的冲突{actions}：终结符{}没有声明优先级", conflict.symbol));
+                    remained.push(conflict);
+                    continue;
+                }
+                (_, None) => {
+                    unresolved.push(format!("{cell}的冲突{actions}：产生式{}不含声明过优先级的终结符", self.grammar.show_production(production)));
+                    remained.push(conflict);
+                    continue;
+                }
+            };
+            let key = (conflict.state, conflict.symbol);
+            let (chosen, reason) = if production_level > symbol_level {
+                (Some(reduce), format!("产生式{}优先于{}", self.grammar.show_production(production), conflict.symbol))
+            } else if production_level < symbol_level {
+                (Some(shift), format!("{}优先于产生式{}", conflict.symbol, self.grammar.show_production(production)))
+            } else {
+                let chosen = match associativity {
+                    Associativity::Left => Some(reduce),
+                    Associativity::Right => Some(shift),
+                    Associativity::NonAssoc => None,
+                };
+                (chosen, format!("{}与产生式{}同级且{associativity}", conflict.symbol, self.grammar.show_production(production)))
+            };
+            let chosen = match chosen {
+                Some(action) => {
+                    self.action.insert(key, action);
+                    action.to_string()
+                }
+                None => {
+                    self.action.remove(&key);
+                    "报错".to_string()
+                }
+            };
+            resolved.push(format!("{cell}的移进-归约冲突{shift}/{reduce}：{reason}，取{chosen}"));
+        }
+        self.conflicts = remained;
+        (resolved, unresolved)
+    }
+    pub fn analyzer(&self, to_parse: &str) -> Result<Vec<String>, String> {
+        //记录每一步的状态栈、符号栈、剩余输入和所做的动作
+        let err = Err("该字符串是不可接受的".to_string());
+        let input = to_parse.chars().chain([PredictionAnalyzer::BEGIN_END_CHAR]).collect::<Vec<_>>();
+        let mut state_stack = vec![0];
+        let mut symbol_stack = vec![PredictionAnalyzer::BEGIN_END_CHAR];
+        let mut position = 0;
+        let mut steps = Vec::new();
+        loop {
+            let state = *state_stack.last().unwrap();
+            let now_char = input[position];
+            let status = format!("状态栈:{:<24} 符号栈:{:<12} 剩余输入:{:>12}",
+                                 state_stack.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" "),
+                                 symbol_stack.iter().collect::<String>(),
+                                 input[position..].iter().collect::<String>());
+            match self.action.get(&(state, now_char)) {
+                Some(LrAction::Shift(target)) => {
+                    steps.push(format!("{status} 动作:移进{now_char}，转入状态{target}"));
+                    state_stack.push(*target);
+                    symbol_stack.push(now_char);
+                    position += 1;
+                }
+                Some(LrAction::Reduce(index)) => {
+                    let (left, body) = self.grammar.production(*index);
+                    state_stack.truncate(state_stack.len() - body.len());
+                    symbol_stack.truncate(symbol_stack.len() - body.len());
+                    let target = match self.goto.get(&(*state_stack.last().unwrap(), *left)) {
+                        Some(target) => *target,
+                        None => return err,
+                    };
+                    steps.push(format!("{status} 动作:用({index}) {}归约，GOTO转入状态{target}", self.grammar.show_production(*index)));
+                    state_stack.push(target);
+                    symbol_stack.push(*left);
+                }
+                Some(LrAction::Accept) => {
+                    steps.push(format!("{status} 动作:接受，分析成功"));
+                    return Ok(steps);
+                }
+                None => return err,
+            }
+        }
+    }
+}
+
+impl Display for LrParsingTable {
+    //左侧为ACTION表，右侧为GOTO表
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let origin = self.grammar.origin_grammar();
+        let mut terminals = collect_ordered_vec(origin.terminal().iter().copied());
+        terminals.push(PredictionAnalyzer::BEGIN_END_CHAR);
+        let non_terminals = origin.ordered_non_terminal();
+        let width = self.action.values().map(|x| x.to_string().len())
+            .chain(self.goto.values().map(|x| x.to_string().len()))
+            .max().unwrap_or(1).max(3);
+        write!(f, "{:>5} |", "")?;
+        terminals.iter().try_for_each(|x| write!(f, " {x:>width$}"))?;
+        write!(f, " |")?;
+        non_terminals.iter().try_for_each(|x| write!(f, " {x:>width$}"))?;
+        writeln!(f)?;
+        for state in 0..self.state_count {
+            write!(f, "{state:>5} |")?;
+            for v_t in &terminals {
+                let cell = self.action.get(&(state, *v_t)).map(|x| x.to_string()).unwrap_or_default();
+                write!(f, " {cell:>width$}")?;
+            }
+            write!(f, " |")?;
+            for v_n in &non_terminals {
+                let cell = self.goto.get(&(state, *v_n)).map(|x| x.to_string()).unwrap_or_default();
+                write!(f, " {cell:>width$}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn test_sentence_using_lr_parser_cli(table: &LrParsingTable) {
+    loop {
+        let mut buffer = String::new();
+        stdin().read_line(&mut buffer).expect("读取时发生错误！");
+        if buffer.is_empty() {
+            break;
+        }
+        let sentence = buffer.trim();
+        let sentence = if sentence == EMPTY_SENTENCE { "" } else { sentence };
+        match table.analyzer(sentence) {
+            Ok(steps) => {
+                println!("该字符串是可接受的");
+                println!("解析过程如下：");
+                steps.into_iter().for_each(|x| println!("{x}"));
+            }
+            Err(e) => {
+                println!("{e}");
+            }
+        }
+    }
+}
+
+pub fn build_lr_grammar_with_args(mut args: StringArgs) -> Result<(PushDownAutomatonGrammar, PrecedenceDeclarations), String> {
+    //在文法参数之外可给出--precedence 声明终结符的优先级与结合性
+    let (mut grammar_args, mut declarations) = (Vec::new(), PrecedenceDeclarations::default());
+    while let Some(mode) = args.next() {
+        if mode == "--precedence" {
+            let val = args.next().ok_or("excepted value of param --precedence")?;
+            declarations = PrecedenceDeclarations::parse(&val)?;
+        } else {
+            grammar_args.push(mode);
+        }
+    }
+    Ok((build_push_down_automaton_grammar_with_args(grammar_args.into_iter()), declarations))
+}
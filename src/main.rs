@@ -10,6 +10,20 @@ mod regular_grammar;
 mod pushdown_automaton_grammar;
 mod prediction_analyzer;
 mod statics;
+mod automaton_learner;
+mod pushdown_automaton;
+mod grammar_transformation;
+mod parse_tree;
+mod cyk_parser;
+mod earley_parser;
+mod lr_automaton;
+mod lr_parser;
+mod operator_precedence;
+mod grammar_symbol;
+mod grammar_file;
+mod lexer;
+mod chomsky_hierarchy;
+mod sentence_generator;
 
 fn main() {
     let args = std::env::args().collect::<Vec<_>>();
@@ -1,8 +1,22 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::stdin;
+
+use crate::automaton_learner::learn_dfa_with_command_args;
+use crate::chomsky_hierarchy::{ChomskyClassification, ChomskyType, is_deterministic_right_linear, Linearity};
+use crate::cyk_parser::{CykParser, test_sentence_using_cyk_parser_cli};
 use crate::deterministic_finite_automaton::{build_dfa_with_command_args, simulate_dfa_in_the_terminal};
-use crate::prediction_analyzer::test_sentence_using_prediction_analyzer_cli;
-use crate::pushdown_automaton_grammar::build_push_down_automaton_grammar_with_args;
+use crate::earley_parser::{EarleyParser, test_sentence_using_earley_parser_cli};
+use crate::lexer::Lexer;
+use crate::lr_automaton::LrAutomaton;
+use crate::lr_parser::{build_lr_grammar_with_args, LrParsingTable, test_sentence_using_lr_parser_cli};
+use crate::operator_precedence::{OperatorPrecedenceParser, test_sentence_using_operator_precedence_cli};
+use crate::prediction_analyzer::{analyze_source_using_prediction_analyzer, test_sentence_using_prediction_analyzer_cli};
+use crate::pushdown_automaton::{AcceptMode, build_pda_with_command_args, simulate_pda_in_the_terminal};
+use crate::pushdown_automaton_grammar::{build_push_down_automaton_grammar_with_args, Ll1ConflictKind, PushDownAutomatonGrammar};
 use crate::r#type::StringArgs;
-use crate::regular_grammar::build_rg_with_args;
+use crate::sentence_generator::XorShiftRandom;
+use crate::regular_grammar::{build_rg_with_args, read_grammar_args};
 
 pub fn main_application(mut args: StringArgs) {
     args.next();
@@ -13,6 +27,24 @@ pub fn main_application(mut args: StringArgs) {
                 "--trans_dfa" => { trans_dfa(args) }
                 "--trans_grammar" => { trans_grammar(args) }
                 "--test_ll1" => { test_ll1(args) }
+                "--learn_dfa" => { learn_dfa(args) }
+                "--test_pda" => { test_pda(args) }
+                "--grammar_to_pda" => { grammar_to_pda(args) }
+                "--pda_to_grammar" => { pda_to_grammar(args) }
+                "--switch_pda_accept" => { switch_pda_accept(args) }
+                "--left_factor" => { left_factor(args) }
+                "--reduce_grammar" => { reduce_grammar(args) }
+                "--normalize_grammar" => { normalize_grammar(args) }
+                "--test_cyk" => { test_cyk(args) }
+                "--to_gnf" => { to_gnf(args) }
+                "--test_earley" => { test_earley(args) }
+                "--lr0" => { lr0(args) }
+                "--test_slr" => { test_slr(args) }
+                "--test_lr1" => { test_lr1(args) }
+                "--test_lalr" => { test_lalr(args) }
+                "--test_op" => { test_op(args) }
+                "--classify_grammar" => { classify_grammar(args) }
+                "--generate_sentences" => { generate_sentences(args) }
                 _ => {}
             }
         }
@@ -24,6 +56,24 @@ pub fn main_application(mut args: StringArgs) {
             trans DFA -> --trans_dfa
             test Grammar -> --trans_grammar
             test LL(1) Grammar -> --test_ll1
+            learn DFA by L* -> --learn_dfa
+            simulate PDA -> --test_pda
+            trans Grammar to PDA -> --grammar_to_pda
+            trans PDA to Grammar -> --pda_to_grammar
+            switch PDA accept mode -> --switch_pda_accept
+            left factor Grammar -> --left_factor
+            remove useless symbols -> --reduce_grammar
+            remove $ and unit productions -> --normalize_grammar
+            test CNF and CYK -> --test_cyk
+            trans Grammar to GNF -> --to_gnf
+            test Earley parser -> --test_earley
+            build LR(0) automaton -> --lr0
+            test SLR(1) Grammar -> --test_slr
+            test LR(1) Grammar -> --test_lr1
+            test LALR(1) Grammar -> --test_lalr
+            test Operator Precedence Grammar -> --test_op
+            classify Grammar by Chomsky hierarchy -> --classify_grammar
+            generate sentences and derivations -> --generate_sentences
             ")
         }
     }
@@ -43,23 +93,411 @@ fn trans_grammar(args: StringArgs) {
     simulate_dfa_in_the_terminal(build_rg_with_args(args).into_dfa().expect("dfa转换失败"));
 }
 
-fn test_ll1(args: StringArgs) {
+fn learn_dfa(args: StringArgs) {
+    match learn_dfa_with_command_args(args) {
+        Ok((dfa, dumps)) => {
+            dumps.into_iter().for_each(|x| println!("{x}"));
+            println!("学习得到的DFA如下");
+            println!("{dfa}");
+        }
+        Err(e) => {
+            println!("学习DFA失败！ 原因:{e}");
+        }
+    }
+}
+
+fn test_pda(args: StringArgs) {
+    let pda = build_pda_with_command_args(args);
+    println!("{pda}");
+    simulate_pda_in_the_terminal(pda);
+}
+
+fn grammar_to_pda(args: StringArgs) {
+    match build_push_down_automaton_grammar_with_args(args).to_pda() {
+        Ok(pda) => {
+            println!("{pda}");
+            simulate_pda_in_the_terminal(pda);
+        }
+        Err(e) => {
+            println!("文法转换为PDA失败！ 原因:{e}");
+        }
+    }
+}
+
+fn pda_to_grammar(args: StringArgs) {
+    let pda = build_pda_with_command_args(args);
+    match pda.to_grammar() {
+        Ok((grammar, mapping)) => {
+            println!("三元组对应的非终结符如下");
+            mapping.into_iter().for_each(|((p, x, q), name)| println!("[{p}{x}{q}]={name}"));
+            println!("构造的文法如下");
+            println!("{grammar}");
+        }
+        Err(e) => {
+            println!("PDA转换为文法失败！ 原因:{e}");
+        }
+    }
+}
+
+fn switch_pda_accept(args: StringArgs) {
+    let pda = build_pda_with_command_args(args);
+    let switched = match pda.accept_mode() {
+        AcceptMode::FinalState => pda.to_empty_stack(),
+        AcceptMode::EmptyStack => pda.to_final_state(),
+    };
+    match switched {
+        Ok(switched) => println!("{switched}"),
+        Err(e) => println!("转换接受方式失败！ 原因:{e}"),
+    }
+}
+
+fn left_factor(args: StringArgs) {
     let push_down_gmr = build_push_down_automaton_grammar_with_args(args);
+    match push_down_gmr.left_factor() {
+        Ok((factored, report)) => {
+            println!("提取左因子的过程如下");
+            report.into_iter().for_each(|x| println!("{x}"));
+            println!("提取左因子后的文法如下");
+            println!("{factored}");
+        }
+        Err(e) => {
+            println!("提取左因子失败！ 原因:{e}");
+        }
+    }
+}
+
+fn reduce_grammar(args: StringArgs) {
+    let (reduced, report) = build_push_down_automaton_grammar_with_args(args).reduce();
+    if report.is_empty() {
+        println!("该文法不含无用符号");
+    } else {
+        println!("删除的无用符号如下");
+        report.into_iter().for_each(|x| println!("{x}"));
+    }
+    println!("化简后的文法如下");
+    println!("{reduced}");
+}
+
+fn normalize_grammar(args: StringArgs) {
+    let push_down_gmr = build_push_down_automaton_grammar_with_args(args);
+    println!("可推导出空串的非终结符为{}", push_down_gmr.symbol_table().show_set(&push_down_gmr.get_nullable_set()));
+    match push_down_gmr.eliminate_empty_production() {
+        Ok(without_empty) => {
+            println!("消除空产生式后的文法如下");
+            println!("{without_empty}");
+            println!("消除单产生式后的文法如下");
+            println!("{}", without_empty.eliminate_unit_production());
+        }
+        Err(e) => {
+            println!("消除空产生式失败！ 原因:{e}");
+        }
+    }
+}
+
+fn test_cyk(args: StringArgs) {
+    match CykParser::new(&build_push_down_automaton_grammar_with_args(args)) {
+        Ok(parser) => {
+            println!("乔姆斯基范式如下");
+            println!("{}", parser.grammar());
+            println!("正在进入shell模式");
+            test_sentence_using_cyk_parser_cli(&parser);
+        }
+        Err(e) => {
+            println!("转换为乔姆斯基范式失败！ 原因:{e}");
+        }
+    }
+}
+
+fn to_gnf(args: StringArgs) {
+    match build_push_down_automaton_grammar_with_args(args).to_greibach_normal_form() {
+        Ok(gnf) => {
+            println!("格雷巴赫范式如下");
+            println!("{gnf}");
+        }
+        Err(e) => {
+            println!("转换为格雷巴赫范式失败！ 原因:{e}");
+        }
+    }
+}
+
+fn test_earley(args: StringArgs) {
+    let parser = EarleyParser::new(&build_push_down_automaton_grammar_with_args(args));
+    println!("Earley分析器已构造，正在进入shell模式");
+    test_sentence_using_earley_parser_cli(&parser);
+}
+
+fn lr0(args: StringArgs) {
+    let automaton = match LrAutomaton::build_lr0(&build_push_down_automaton_grammar_with_args(args)) {
+        Ok(automaton) => automaton,
+        Err(e) => {
+            println!("构造LR(0)项目集规范族失败！ 原因:{e}");
+            return;
+        }
+    };
+    println!("拓广文法如下");
+    println!("{}", automaton.grammar());
+    println!("LR(0)项目集规范族如下");
+    println!("{automaton}");
+    match automaton.to_dfa() {
+        Ok((dfa, names)) => {
+            names.into_iter().enumerate().for_each(|(index, name)| println!("I{index}={name}"));
+            println!("识别活前缀的DFA如下");
+            println!("{dfa}");
+            simulate_dfa_in_the_terminal(dfa);
+        }
+        Err(e) => {
+            println!("{e}");
+        }
+    }
+}
+
+fn test_slr(args: StringArgs) {
+    test_lr_grammar("SLR(1)", args, LrParsingTable::build_slr1);
+}
+
+fn test_lr1(args: StringArgs) {
+    test_lr_grammar("LR(1)", args, LrParsingTable::build_lr1);
+}
+
+fn test_lalr(args: StringArgs) {
+    test_lr_grammar("LALR(1)", args, LrParsingTable::build_lalr1);
+}
+
+fn test_lr_grammar<T: Display, B: Fn(&PushDownAutomatonGrammar) -> Result<(T, LrParsingTable), String>>(kind: &str, args: StringArgs, builder: B) {
+    let (automaton, mut table, declarations) = match build_lr_grammar_with_args(args).and_then(|(grammar, declarations)| builder(&grammar).map(|(x, y)| (x, y, declarations))) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("构造{kind}分析表失败！ 原因:{e}");
+            return;
+        }
+    };
+    let (resolved, unresolved) = table.resolve_conflicts(&declarations);
+    println!("拓广文法如下");
+    println!("{}", table.grammar());
+    println!("项目集规范族如下");
+    println!("{automaton}");
+    println!("{kind}分析表如下");
+    println!("{table}");
+    if !resolved.is_empty() {
+        println!("以下冲突已按优先级与结合性解决");
+        resolved.iter().for_each(|x| println!("{x}"));
+    }
+    if !declarations.is_empty() && !unresolved.is_empty() {
+        println!("以下冲突无法按优先级与结合性解决");
+        unresolved.iter().for_each(|x| println!("{x}"));
+    }
+    if !table.conflicts().is_empty() {
+        println!("该文法不是{kind}文法，冲突如下");
+        table.conflicts().iter().for_each(|x| print!("{x}"));
+        if table.conflicts().iter().any(|x| x.is_introduced_by_merge()) {
+            println!("部分冲突由合并同心项目集引入，可尝试使用LR(1)分析 --test_lr1");
+        }
+        if declarations.is_empty() && table.conflicts().iter().any(|x| x.is_shift_reduce()) {
+            println!("移进-归约冲突可通过 --precedence 声明优先级与结合性解决，如 --precedence \"left:+;left:*\"");
+        }
+        return;
+    }
+    println!("该文法是{kind}文法");
+    test_sentence_using_lr_parser_cli(&table);
+}
+
+fn test_op(args: StringArgs) {
+    let parser = match OperatorPrecedenceParser::new(&build_push_down_automaton_grammar_with_args(args)) {
+        Ok(parser) => parser,
+        Err(e) => {
+            println!("该文法不是算符文法！ 原因:{e}");
+            return;
+        }
+    };
+    println!("FIRSTVT、LASTVT集合及算符优先关系表如下");
+    println!("{parser}");
+    let conflicts = parser.conflicts();
+    if !conflicts.is_empty() {
+        println!("该文法不是算符优先文法，冲突如下");
+        conflicts.iter().for_each(|x| println!("{x}"));
+        return;
+    }
+    println!("该文法是算符优先文法");
+    test_sentence_using_operator_precedence_cli(&parser);
+}
+
+fn test_ll1(mut args: StringArgs) {
+    //--source 给出要分析的源文件，--token-map 将词法分析得到的记号种类映射为文法中的终结符
+    let (mut grammar_args, mut source, mut kind_map) = (Vec::new(), None, HashMap::new());
+    while let Some(mode) = args.next() {
+        match mode.as_str() {
+            "--source" => {
+                source = Some(args.next().expect("excepted value of param --source"));
+            }
+            "--token-map" => {
+                let val = args.next().expect("excepted value of param --token-map");
+                kind_map = Lexer::parse_kind_map(&val).expect("解析记号种类映射时发生错误");
+            }
+            _ => grammar_args.push(mode),
+        }
+    }
+    test_ll1_grammar(build_push_down_automaton_grammar_with_args(grammar_args.into_iter()), source.as_deref(), &kind_map);
+}
+
+fn test_ll1_grammar(push_down_gmr: PushDownAutomatonGrammar, source: Option<&str>, kind_map: &HashMap<String, String>) {
     match push_down_gmr.build_ll1_analyzer() {
         Ok((ll1_grammar, first, follow, select)) => {
+            let ll1_grammar = ll1_grammar.with_synch_set(&follow);
+            let symbols = push_down_gmr.symbol_table();
             println!("first set 如下");
-            first.into_iter().map(|(ch, set)| format!("FIRST({ch})={:?}", set)).for_each(|x| println!("{x}"));
+            first.iter().map(|(ch, set)| format!("FIRST({})={}", symbols.name(*ch), symbols.show_set(set))).for_each(|x| println!("{x}"));
             println!("follow set 如下");
-            follow.into_iter().map(|(ch, set)| format!("FOLLOW({ch})={:?}", set)).for_each(|x| println!("{x}"));
+            follow.iter().map(|(ch, set)| format!("FOLLOW({})={}", symbols.name(*ch), symbols.show_set(set))).for_each(|x| println!("{x}"));
             println!("select set 如下");
-            select.into_iter().map(|((from, to), set)| format!("SELECT({from}->{to})={:?}", set)).for_each(|x| println!("{x}"));
+            select.iter().map(|((from, to), set)| format!("SELECT({}->{})={}", symbols.name(*from), symbols.show_production(to), symbols.show_set(set))).for_each(|x| println!("{x}"));
             println!("预测分析表如下 ");
             println!("{ll1_grammar}");
+            let lexer = Lexer::new(&push_down_gmr).with_kind_map(kind_map.clone());
+            if let Some(path) = source {
+                match std::fs::read_to_string(path) {
+                    Ok(text) => analyze_source_using_prediction_analyzer(&ll1_grammar, &lexer, &text),
+                    Err(e) => println!("读取源文件{path}时发生错误：{e}"),
+                }
+                return;
+            }
             println!("该文法是LL(1)文法，正在进入shell模式");
-            test_sentence_using_prediction_analyzer_cli(&ll1_grammar);
+            test_sentence_using_prediction_analyzer_cli(&ll1_grammar, &lexer);
         }
         Err(e) => {
             println!("该文法不是LL(1)文法！ 原因:{e}");
+            if let Ok(conflicts) = push_down_gmr.get_ll1_conflicts() {
+                let first_first = conflicts.iter().filter(|x| x.kind() == Ll1ConflictKind::FirstFirst).count();
+                println!("共{}处冲突，其中FIRST/FIRST冲突{first_first}处，FIRST/FOLLOW冲突{}处", conflicts.len(), conflicts.len() - first_first);
+            }
+            if push_down_gmr.has_left_recursion() {
+                match push_down_gmr.eliminate_left_recursion() {
+                    Ok(transformed) => {
+                        println!("该文法含有左递归，消除左递归后的文法如下");
+                        println!("{transformed}");
+                        if ask_yes_or_no("是否使用消除左递归后的文法重新构造LL(1)分析表？(y/n)") {
+                            test_ll1_grammar(transformed, source, kind_map);
+                        }
+                    }
+                    Err(e) => {
+                        println!("消除左递归失败！ 原因:{e}");
+                    }
+                }
+            } else if push_down_gmr.has_common_prefix() {
+                match push_down_gmr.left_factor() {
+                    Ok((factored, report)) => {
+                        println!("该文法的候选式含有公共左因子，提取过程如下");
+                        report.into_iter().for_each(|x| println!("{x}"));
+                        println!("{factored}");
+                        if ask_yes_or_no("是否使用提取左因子后的文法重新构造LL(1)分析表？(y/n)") {
+                            test_ll1_grammar(factored, source, kind_map);
+                        }
+                    }
+                    Err(e) => {
+                        println!("提取左因子失败！ 原因:{e}");
+                    }
+                }
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+fn classify_grammar(args: StringArgs) {
+    //按文法类型选择分析工具：确定的右线性文法转换为DFA，上下文无关文法优先使用LL(1)分析，否则使用Earley分析
+    let args = args.collect::<Vec<_>>();
+    if let Some(mode) = args.iter().find(|x| *x == "--symbols" || *x == "--grammar-file") {
+        println!("文法分类只支持以--grammar给出的单字符文法符号，不支持{mode}");
+        return;
+    }
+    let (grammar_str, start) = read_grammar_args(args.clone().into_iter());
+    let classification = match ChomskyClassification::classify(&grammar_str, start) {
+        Ok(classification) => classification,
+        Err(e) => {
+            println!("无法识别该文法！ 原因:{e}");
+            return;
+        }
+    };
+    print!("{classification}");
+    match classification.grammar_type() {
+        ChomskyType::Type3(Linearity::Right) if is_deterministic_right_linear(&grammar_str) => {
+            println!("正在将正规文法转换为DFA");
+            trans_grammar(args.into_iter());
+        }
+        ChomskyType::Type3(_) | ChomskyType::Type2 => {
+            let push_down_gmr = build_push_down_automaton_grammar_with_args(args.into_iter());
+            if push_down_gmr.build_ll1_analyzer().is_ok() {
+                println!("正在使用LL(1)预测分析");
+                test_ll1_grammar(push_down_gmr, None, &HashMap::new());
+            } else {
+                println!("该文法不是LL(1)文法，正在使用Earley分析器");
+                test_sentence_using_earley_parser_cli(&EarleyParser::new(&push_down_gmr));
+            }
+        }
+        grammar_type => {
+            println!("暂不支持{grammar_type}的句子分析");
+        }
+    }
+}
+
+fn generate_sentences(mut args: StringArgs) {
+    //--max_length --limit 控制按长度枚举的句子，--random --depth --seed 控制随机生成的句子
+    let mut grammar_args = Vec::new();
+    let (mut max_length, mut limit) = (PushDownAutomatonGrammar::DEFAULT_SENTENCE_LENGTH, PushDownAutomatonGrammar::DEFAULT_SENTENCE_LIMIT);
+    let (mut random_count, mut depth, mut seed) = (0, PushDownAutomatonGrammar::DEFAULT_DERIVATION_DEPTH, None);
+    while let Some(mode) = args.next() {
+        let number = |val: Option<String>| val.and_then(|x| x.parse::<usize>().ok()).unwrap_or_else(|| panic!("excepted number value of param {mode}"));
+        match mode.as_str() {
+            "--max_length" => max_length = number(args.next()),
+            "--limit" => limit = number(args.next()),
+            "--random" => random_count = number(args.next()),
+            "--depth" => depth = number(args.next()),
+            "--seed" => seed = Some(number(args.next()) as u64),
+            _ => grammar_args.push(mode),
+        }
+    }
+    let push_down_gmr = build_push_down_automaton_grammar_with_args(grammar_args.into_iter());
+    match push_down_gmr.enumerate_sentences(max_length, limit) {
+        Ok(sentences) => {
+            println!("长度不超过{max_length}的句子如下（至多{limit}个）");
+            sentences.iter().for_each(|x| println!("{x}"));
+        }
+        Err(e) => println!("枚举句子失败！ 原因:{e}"),
+    }
+    let mut random = seed.map(XorShiftRandom::new).unwrap_or_else(XorShiftRandom::from_time);
+    if random_count > 0 {
+        println!("随机生成的句子如下（推导深度约为{depth}）");
+    }
+    for _ in 0..random_count {
+        match push_down_gmr.generate_random_sentence(depth, &mut random) {
+            Ok(sentence) => println!("{sentence}"),
+            Err(e) => {
+                println!("随机生成句子失败！ 原因:{e}");
+                break;
+            }
+        }
+    }
+    println!("输入句子以输出其最左推导与最右推导");
+    loop {
+        let mut buffer = String::new();
+        stdin().read_line(&mut buffer).expect("读取时发生错误！");
+        if buffer.is_empty() {
+            break;
+        }
+        match push_down_gmr.get_derivations(buffer.trim()) {
+            Ok(derivations) => {
+                if derivations.is_ambiguous() {
+                    println!("该句子是二义的，以下推导对应其中一棵语法树");
+                }
+                println!("最左推导：{}", derivations.leftmost().join(" => "));
+                println!("最右推导：{}", derivations.rightmost().join(" => "));
+            }
+            Err(e) => println!("{e}"),
+        }
+    }
+}
+
+fn ask_yes_or_no(question: &str) -> bool {
+    println!("{question}");
+    let mut buffer = String::new();
+    stdin().read_line(&mut buffer).expect("读取时发生错误！");
+    matches!(buffer.trim(), "y" | "Y" | "yes")
+}
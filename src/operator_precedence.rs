@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::io::stdin;
+
+use crate::prediction_analyzer::PredictionAnalyzer;
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::statics::GRAMMAR_SPLIT_IO_UNIT;
+use crate::utils::{collect_ordered_vec, join_production};
+
+pub type VtSet = HashMap<char, HashSet<char>>;
+//FIRSTVT或LASTVT集合
+
+impl PushDownAutomatonGrammar {
+    pub fn check_operator_grammar(&self) -> Result<(), String> {
+        //算符文法：右部不含两个相邻的非终结符，且不含空产生式
+        let productions = self.symbol_productions();
+        for left_v_n in self.ordered_non_terminal() {
+            for body in productions.get(&left_v_n).into_iter().flatten() {
+                let shown = format!("{left_v_n}{GRAMMAR_SPLIT_IO_UNIT}{}", join_production(body));
+                if body.is_empty() {
+                    return Err(format!("{shown} 算符文法不能含有空产生式"));
+                }
+                if body.windows(2).any(|x| self.non_terminal().contains(&x[0]) && self.non_terminal().contains(&x[1])) {
+                    return Err(format!("{shown} 右部含有相邻的非终结符"));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn get_vt_set(&self, reversed: bool) -> VtSet {
+        //P->a...或P->Qa...则a属于FIRSTVT(P)；P->Q...则FIRSTVT(Q)包含于FIRSTVT(P)
+        //LASTVT将右部反转后同理计算
+        let productions = self.symbol_productions();
+        let mut vt_set: VtSet = self.non_terminal().iter().map(|x| (*x, HashSet::new())).collect();
+        loop {
+            let mut closed = true;
+            for (left_v_n, bodies) in &productions {
+                for body in bodies {
+                    let body = if reversed { body.iter().rev().copied().collect::<Vec<_>>() } else { body.clone() };
+                    let mut found = HashSet::new();
+                    match body.as_slice() {
+                        [first, ..] if self.terminal().contains(first) => { found.insert(*first); }
+                        [first, rest @ ..] => {
+                            found.extend(vt_set.get(first).cloned().unwrap_or_default());
+                            if let Some(second) = rest.first().filter(|x| self.terminal().contains(x)) {
+                                found.insert(*second);
+                            }
+                        }
+                        [] => {}
+                    }
+                    let left_set = vt_set.entry(*left_v_n).or_default();
+                    if !found.is_subset(left_set) {
+                        left_set.extend(found);
+                        closed = false;
+                    }
+                }
+            }
+            if closed {
+                break;
+            }
+        }
+        vt_set
+    }
+    pub fn get_firstvt_set(&self) -> VtSet {
+        self.get_vt_set(false)
+    }
+    pub fn get_lastvt_set(&self) -> VtSet {
+        self.get_vt_set(true)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum PrecedenceRelation {
+    Less,
+    Equal,
+    Greater,
+}
+
+impl Display for PrecedenceRelation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrecedenceRelation::Less => write!(f, "⋖"),
+            PrecedenceRelation::Equal => write!(f, "≐"),
+            PrecedenceRelation::Greater => write!(f, "⋗"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OperatorPrecedenceParser {
+    grammar: PushDownAutomatonGrammar,
+    firstvt_set: VtSet,
+    lastvt_set: VtSet,
+    relations: BTreeMap<(char, char), Vec<PrecedenceRelation>>, //两个终结符之间的全部优先关系，多于一个即为冲突
+}
+
+impl OperatorPrecedenceParser {
+    pub fn new(grammar: &PushDownAutomatonGrammar) -> Result<Self, String> {
+        grammar.check_single_char_symbols("算符优先分析")?;
+        grammar.check_operator_grammar()?;
+        let firstvt_set = grammar.get_firstvt_set();
+        let lastvt_set = grammar.get_lastvt_set();
+        let mut relations: BTreeMap<(char, char), Vec<PrecedenceRelation>> = BTreeMap::new();
+        let mut add = |left: char, right: char, relation: PrecedenceRelation| {
+            let cell = relations.entry((left, right)).or_default();
+            if !cell.contains(&relation) {
+                cell.push(relation);
+                cell.sort();
+            }
+        };
+        //视为拓广了产生式S'->#S#
+        let end = PredictionAnalyzer::BEGIN_END_CHAR;
+        let mut bodies = vec![vec![end, grammar.start(), end]];
+        bodies.extend(grammar.symbol_productions().into_values().flatten());
+        let is_terminal = |x: &char| !grammar.non_terminal().contains(x);
+        for body in &bodies {
+            for (index, now) in body.iter().enumerate() {
+                let next = match body.get(index + 1) {
+                    Some(next) => next,
+                    None => break,
+                };
+                if is_terminal(now) && is_terminal(next) {
+                    add(*now, *next, PrecedenceRelation::Equal);
+                }
+                if let Some(after) = body.get(index + 2).filter(|x| is_terminal(now) && !is_terminal(next) && is_terminal(x)) {
+                    add(*now, *after, PrecedenceRelation::Equal);
+                }
+                if is_terminal(now) && !is_terminal(next) {
+                    firstvt_set[next].iter().for_each(|x| add(*now, *x, PrecedenceRelation::Less));
+                }
+                if !is_terminal(now) && is_terminal(next) {
+                    lastvt_set[now].iter().for_each(|x| add(*x, *next, PrecedenceRelation::Greater));
+                }
+            }
+        }
+        Ok(OperatorPrecedenceParser {
+            grammar: grammar.clone(),
+            firstvt_set,
+            lastvt_set,
+            relations,
+        })
+    }
+    pub fn relation(&self, left: char, right: char) -> Option<PrecedenceRelation> {
+        self.relations.get(&(left, right)).and_then(|x| x.first()).copied()
+    }
+    pub fn conflicts(&self) -> Vec<String> {
+        self.relations.iter()
+            .filter(|(_, x)| x.len() > 1)
+            .map(|((left, right), x)| format!("{left}与{right}之间存在多种优先关系: {}", x.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ")))
+            .collect()
+    }
+    fn is_terminal(&self, symbol: char) -> bool {
+        !self.grammar.non_terminal().contains(&symbol)
+    }
+    fn reduce_target(&self, phrase: &[char]) -> Option<(char, Vec<char>)> {
+        //算符优先分析不关心非终结符的名字，只要求终结符与非终结符的位置与某个产生式一致
+        let shape_matches = |body: &Vec<char>| body.len() == phrase.len() && body.iter().zip(phrase)
+            .all(|(x, y)| if self.is_terminal(*x) { x == y } else { !self.is_terminal(*y) });
+        let productions = self.grammar.symbol_productions();
+        self.grammar.ordered_non_terminal().into_iter()
+            .find_map(|left_v_n| productions.get(&left_v_n).into_iter().flatten().find(|x| shape_matches(x)).map(|x| (left_v_n, x.clone())))
+    }
+    pub fn analyzer(&self, to_parse: &str) -> Result<Vec<String>, String> {
+        //栈顶终结符⋗当前输入时，向下找到满足S[j]⋖Q的S[j]，S[j+1..]即为最左素短语
+        let err = Err("该字符串是不可接受的".to_string());
+        let end = PredictionAnalyzer::BEGIN_END_CHAR;
+        let input = to_parse.chars().chain([end]).collect::<Vec<_>>();
+        let mut stack = vec![end];
+        let mut position = 0;
+        let mut steps = Vec::new();
+        loop {
+            let now_char = input[position];
+            let top_terminal = stack.iter().rposition(|x| self.is_terminal(*x)).unwrap();
+            let top_char = stack[top_terminal];
+            let status = format!("符号栈:{:<16} 剩余输入:{:>16}", stack.iter().collect::<String>(), input[position..].iter().collect::<String>());
+            if top_char == end && now_char == end {
+                return if stack.len() == 2 {
+                    steps.push(format!("{status} 动作:接受，分析成功"));
+                    Ok(steps)
+                } else {
+                    err
+                };
+            }
+            match self.relation(top_char, now_char) {
+                Some(PrecedenceRelation::Greater) => {
+                    let mut j = top_terminal;
+                    loop {
+                        let q = stack[j];
+                        j = match stack[..j].iter().rposition(|x| self.is_terminal(*x)) {
+                            Some(j) => j,
+                            None => return err,
+                        };
+                        if self.relation(stack[j], q) == Some(PrecedenceRelation::Less) {
+                            break;
+                        }
+                    }
+                    let phrase = stack.split_off(j + 1);
+                    let (left_v_n, body) = match self.reduce_target(&phrase) {
+                        Some(x) => x,
+                        None => return err,
+                    };
+                    steps.push(format!("{status} 动作:{top_char}{}{now_char}，归约最左素短语{}为{left_v_n}（{left_v_n}{GRAMMAR_SPLIT_IO_UNIT}{}）",
+                                       PrecedenceRelation::Greater, phrase.iter().collect::<String>(), join_production(&body)));
+                    stack.push(left_v_n);
+                }
+                Some(relation) => {
+                    steps.push(format!("{status} 动作:{top_char}{relation}{now_char}，移进{now_char}"));
+                    stack.push(now_char);
+                    position += 1;
+                }
+                None => return err,
+            }
+        }
+    }
+}
+
+impl Display for OperatorPrecedenceParser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |x: &HashSet<char>| collect_ordered_vec(x.iter().copied()).into_iter().map(String::from).collect::<Vec<_>>().join(",");
+        for v_n in self.grammar.ordered_non_terminal() {
+            writeln!(f, "FIRSTVT({v_n})={{{}}}  LASTVT({v_n})={{{}}}", show(&self.firstvt_set[&v_n]), show(&self.lastvt_set[&v_n]))?;
+        }
+        let mut terminals = collect_ordered_vec(self.grammar.terminal().iter().copied());
+        terminals.push(PredictionAnalyzer::BEGIN_END_CHAR);
+        write!(f, "   |")?;
+        terminals.iter().try_for_each(|x| write!(f, " {x:>3}"))?;
+        writeln!(f)?;
+        for left in &terminals {
+            write!(f, "{left:>2} |")?;
+            for right in &terminals {
+                let cell = self.relations.get(&(*left, *right)).map(|x| x.iter().map(|x| x.to_string()).collect::<String>()).unwrap_or_default();
+                write!(f, " {cell:>3}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn test_sentence_using_operator_precedence_cli(parser: &OperatorPrecedenceParser) {
+    loop {
+        let mut buffer = String::new();
+        stdin().read_line(&mut buffer).expect("读取时发生错误！");
+        if buffer.is_empty() {
+            break;
+        }
+        match parser.analyzer(buffer.trim()) {
+            Ok(steps) => {
+                println!("该字符串是可接受的");
+                println!("解析过程如下：");
+                steps.into_iter().for_each(|x| println!("{x}"));
+            }
+            Err(e) => {
+                println!("{e}");
+            }
+        }
+    }
+}
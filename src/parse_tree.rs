@@ -0,0 +1,73 @@
+use std::fmt::{Display, Formatter};
+
+use crate::statics::EMPTY_SENTENCE;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ParseTree {
+    symbol: String, //结点上的文法符号，叶子为终结符或空串
+    children: Vec<ParseTree>, //按产生式右部的顺序排列的子结点
+}
+
+impl ParseTree {
+    pub fn new<S: Into<String>>(symbol: S, children: Vec<ParseTree>) -> Self {
+        ParseTree {
+            symbol: symbol.into(),
+            children,
+        }
+    }
+    pub fn leaf<S: Into<String>>(symbol: S) -> Self {
+        ParseTree::new(symbol, Vec::new())
+    }
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+    pub fn derivation(&self, leftmost: bool) -> Vec<Vec<&str>> {
+        //每一步展开最左（或最右）的内部结点，得到推导过程中的各个句型，空串叶子不出现在句型中
+        let mut form = vec![self];
+        let mut forms = vec![vec![self.symbol()]];
+        loop {
+            let mut inner = form.iter().enumerate().filter(|(_, x)| !x.children.is_empty()).map(|(i, _)| i);
+            let index = match if leftmost { inner.next() } else { inner.next_back() } {
+                Some(index) => index,
+                None => break,
+            };
+            let children = form[index].children.iter().filter(|x| !(x.children.is_empty() && x.symbol == EMPTY_SENTENCE));
+            form.splice(index..=index, children);
+            forms.push(form.iter().map(|x| x.symbol()).collect());
+        }
+        forms
+    }
+    pub fn to_dot(&self) -> String {
+        //结点按先序编号，叶子结点不加边框
+        let mut lines = vec!["digraph ParseTree {".to_string()];
+        self.write_dot(&mut lines, &mut 0);
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+    fn write_dot(&self, lines: &mut Vec<String>, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let label = self.symbol.replace('\\', "\\\\").replace('"', "\\\"");
+        let shape = if self.children.is_empty() { ", shape=plaintext" } else { "" };
+        lines.push(format!("    node{id} [label=\"{label}\"{shape}];"));
+        for child in &self.children {
+            let child_id = child.write_dot(lines, next_id);
+            lines.push(format!("    node{id} -> node{child_id};"));
+        }
+        id
+    }
+    fn fmt_with_indent(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        writeln!(f, "{}{}", "  ".repeat(depth), self.symbol)?;
+        for child in &self.children {
+            child.fmt_with_indent(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ParseTree {
+    //以缩进表示层次
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with_indent(f, 0)
+    }
+}
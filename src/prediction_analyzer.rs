@@ -1,9 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::io::stdin;
-use std::ops::Add;
 
-use crate::statics::EMPTY_SENTENCE;
+use crate::grammar_symbol::SymbolTable;
+use crate::lexer::{line_column, Lexer, Token};
+use crate::parse_tree::ParseTree;
+use crate::statics::{EMPTY_SENTENCE, EMPTY_SENTENCE_CHAR, GRAMMAR_SPLIT_IO_UNIT};
+use crate::utils::collect_ordered_vec;
 
 #[derive(Hash, Eq, PartialEq, Debug, Default)]
 pub struct PredictionAnalyzerInput {
@@ -20,76 +23,350 @@ impl PredictionAnalyzerInput {
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AnalysisAction {
+    Derive(String, String), //用产生式A->α推导
+    Match(String), //栈顶终结符与输入符号匹配
+    Accept,
+    Skip(String), //错误恢复：跳过输入符号
+    Pop(String), //错误恢复：弹出不匹配的栈顶终结符
+    Synch(String), //错误恢复：遇到同步符号，弹出栈顶非终结符
+    Finish, //有错误时分析结束
+}
+
+impl Display for AnalysisAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisAction::Derive(v_n, target) => write!(f, "{v_n}{GRAMMAR_SPLIT_IO_UNIT}{target}"),
+            AnalysisAction::Match(v_t) => write!(f, "{v_t}匹配"),
+            AnalysisAction::Accept => write!(f, "接受"),
+            AnalysisAction::Skip(v_t) => write!(f, "出错，跳过{v_t}"),
+            AnalysisAction::Pop(v_t) => write!(f, "出错，弹出{v_t}"),
+            AnalysisAction::Synch(v_n) => write!(f, "synch，弹出{v_n}"),
+            AnalysisAction::Finish => write!(f, "分析结束"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AnalysisStep {
+    step: usize,
+    stack: String, //分析栈，栈底在左
+    remaining: String, //剩余输入串
+    action: AnalysisAction, //所用产生式或所做的动作
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StepTableFormat {
+    Text,
+    Markdown,
+    Csv,
+}
+
+fn display_width(text: &str) -> usize {
+    //中文等全角字符按两个字符宽度计算
+    text.chars().map(|x| if x > '\u{1100}' { 2 } else { 1 }).sum()
+}
+
+pub fn render_analysis_steps(steps: &[AnalysisStep], format: StepTableFormat) -> String {
+    let header = ["步骤", "分析栈", "剩余输入串", "所用产生式"].map(String::from);
+    let rows = steps.iter()
+        .map(|x| [x.step.to_string(), x.stack.clone(), x.remaining.clone(), x.action.to_string()])
+        .collect::<Vec<_>>();
+    match format {
+        StepTableFormat::Text => {
+            //步骤与分析栈左对齐，剩余输入串右对齐
+            let widths = (0..header.len())
+                .map(|i| rows.iter().chain([&header]).map(|x| display_width(&x[i])).max().unwrap())
+                .collect::<Vec<_>>();
+            [&header].into_iter().chain(&rows).map(|row| row.iter().enumerate().map(|(i, cell)| {
+                let padding = " ".repeat(widths[i] - display_width(cell));
+                if i == 2 { format!("{padding}{cell}") } else { format!("{cell}{padding}") }
+            }).collect::<Vec<_>>().join("  ").trim_end().to_string()).collect::<Vec<_>>().join("\n")
+        }
+        StepTableFormat::Markdown => {
+            let show = |row: &[String; 4]| format!("| {} |", row.iter().map(|x| x.replace('|', "\\|")).collect::<Vec<_>>().join(" | "));
+            [show(&header), "| --- | --- | ---: | --- |".to_string()].into_iter()
+                .chain(rows.iter().map(show))
+                .collect::<Vec<_>>().join("\n")
+        }
+        StepTableFormat::Csv => {
+            let escape = |x: &String| if x.contains([',', '"', '\n']) { format!("\"{}\"", x.replace('"', "\"\"")) } else { x.clone() };
+            [&header].into_iter().chain(&rows)
+                .map(|row| row.iter().map(escape).collect::<Vec<_>>().join(","))
+                .collect::<Vec<_>>().join("\n")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SyntaxError {
+    position: usize, //出错的输入符号在输入串中的字符下标
+    reason: String,
+    expected: Vec<String>, //此时可以接受的终结符
+}
+
+impl SyntaxError {
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let expected = self.expected.join(" ");
+        write!(f, "该字符串是不可接受的 第{}个字符处出错，{}，期望的终结符为 {{{expected}}}", self.position + 1, self.reason)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct PredictionAnalyzer {
     analyzer_table: HashMap<PredictionAnalyzerInput, String>,
     start_char: char,
+    synch_set: HashMap<char, HashSet<char>>, //分析表中为synch的表项
+    symbol_table: SymbolTable, //多字符文法符号的名字
+    non_terminal: HashSet<char>, //分析表的行即为全部非终结符
 }
 
 impl PredictionAnalyzer {
     pub fn new(analyzer_table: HashMap<PredictionAnalyzerInput, String>, start_char: char) -> Self {
+        let non_terminal = analyzer_table.keys().map(|x| x.v_n).collect();
         PredictionAnalyzer {
             analyzer_table,
             start_char,
+            synch_set: HashMap::new(),
+            symbol_table: SymbolTable::default(),
+            non_terminal,
+        }
+    }
+    pub fn with_symbol_table(mut self, symbol_table: SymbolTable) -> Self {
+        self.symbol_table = symbol_table;
+        self
+    }
+    fn is_non_terminal(&self, symbol: char) -> bool {
+        self.non_terminal.contains(&symbol)
+    }
+    fn tokenize(&self, to_parse: &str) -> Vec<Token> {
+        //未经词法分析的输入串，含多字符符号时以空白分隔，否则逐字符切分，记号的种类即为原文
+        let mut tokens = Vec::new();
+        if self.symbol_table.is_multi_char() {
+            let mut start = None;
+            for (index, ch) in to_parse.chars().chain([' ']).enumerate() {
+                match (ch.is_whitespace(), start) {
+                    (false, None) => start = Some(index),
+                    (true, Some(begin)) => {
+                        let lexeme = to_parse.chars().skip(begin).take(index - begin).collect::<String>();
+                        tokens.push(Token::new(lexeme.clone(), lexeme, begin));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        } else {
+            tokens.extend(to_parse.chars().enumerate().map(|(index, ch)| Token::new(ch, ch, index)));
+        }
+        tokens
+    }
+    fn terminal_code(&self, kind: &str) -> char {
+        //记号种类对应的终结符编码，不是终结符的种类一律无法匹配
+        let mut chars = kind.chars();
+        match (self.symbol_table.code(kind), chars.next(), chars.next()) {
+            (Some(code), _, _) => code,
+            (None, Some(ch), None) if !self.symbol_table.is_multi_char() => ch,
+            _ => char::REPLACEMENT_CHARACTER,
         }
     }
-    pub fn analyzer(&self, to_parse: &String) -> Result<Vec<String>, String> {
-        let mut pull_down_queue = Vec::new();
-        let err = Err("该字符串是不可接受的".to_string());
-        let to_parse = to_parse.clone().add(&String::from(PredictionAnalyzer::BEGIN_END_CHAR));
-        let mut to_parse_iter = to_parse.chars().into_iter();
-        let mut analyzer_stack = Vec::new();
-        analyzer_stack.push(PredictionAnalyzer::BEGIN_END_CHAR);//将文法开始符和边界符依次压入栈中
-        analyzer_stack.push(self.start_char.clone());
-        let mut now_char = to_parse_iter.next().unwrap();//把第一个输入符号读入now_char
+    pub fn with_synch_set(mut self, follow_set: &HashMap<char, HashSet<char>>) -> Self {
+        //将FOLLOW(A)中M[A,b]为空的表项填为synch
+        for (v_n, follow) in follow_set {
+            let synch = follow.iter()
+                .filter(|x| self.analyzer_table.get(&PredictionAnalyzerInput::new(*v_n, **x)).is_none_or(|y| y.is_empty()))
+                .copied()
+                .collect();
+            self.synch_set.insert(*v_n, synch);
+        }
+        self
+    }
+    fn expected_terminals(&self, stack_top: char) -> Vec<String> {
+        //非终结符取分析表中该行的非空表项，终结符只能与自身匹配
+        if self.is_non_terminal(stack_top) {
+            collect_ordered_vec(self.analyzer_table.iter().filter(|(x, y)| x.v_n == stack_top && !y.is_empty()).map(|(x, _)| self.symbol_table.name(x.v_t)))
+        } else {
+            vec![self.symbol_table.name(stack_top)]
+        }
+    }
+    pub fn analyzer(&self, to_parse: &str) -> Result<(Vec<AnalysisStep>, ParseTree), SyntaxError> {
+        self.analyzer_tokens(&self.tokenize(to_parse))
+    }
+    pub fn analyzer_with_recovery(&self, to_parse: &str) -> (Vec<AnalysisStep>, ParseTree, Vec<SyntaxError>) {
+        self.analyzer_tokens_with_recovery(&self.tokenize(to_parse))
+    }
+    pub fn analyzer_tokens(&self, tokens: &[Token]) -> Result<(Vec<AnalysisStep>, ParseTree), SyntaxError> {
+        //分析词法分析器给出的记号流，记号的种类即为文法中的终结符
+        let (steps, tree, errors) = self.analyze(tokens, false);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok((steps, tree)),
+        }
+    }
+    pub fn analyzer_tokens_with_recovery(&self, tokens: &[Token]) -> (Vec<AnalysisStep>, ParseTree, Vec<SyntaxError>) {
+        //恐慌模式：出错后跳过输入符号或弹出栈顶符号继续分析，返回全部错误及部分语法树
+        self.analyze(tokens, true)
+    }
+    fn analyze(&self, tokens: &[Token], recover: bool) -> (Vec<AnalysisStep>, ParseTree, Vec<SyntaxError>) {
+        let mut steps = Vec::new();
+        let mut errors = Vec::new();
+        let mut recovering = false;//从出错到下一次成功匹配之间不重复报错
+        //记号流末尾补上边界符，其位置紧接最后一个记号
+        let end = PredictionAnalyzer::BEGIN_END_CHAR;
+        let end_position = tokens.last().map(|x| x.position() + x.lexeme().chars().count()).unwrap_or(0);
+        let tokens = tokens.iter()
+            .map(|x| (self.terminal_code(x.kind()), x.lexeme().to_string(), x.position()))
+            .chain([(end, end.to_string(), end_position)])
+            .collect::<Vec<_>>();
+        let name = |x: char| self.symbol_table.name(x);
+        let mut position = 0;//当前输入符号的位置
+        //语法树结点按创建顺序存放，nodes[i]=(文法符号,子结点编号)，0号为根结点
+        let mut nodes: Vec<(char, Vec<usize>)> = vec![(self.start_char, Vec::new())];
+        let mut analyzer_stack = Vec::new();//栈中同时记录符号对应的语法树结点
+        analyzer_stack.push((PredictionAnalyzer::BEGIN_END_CHAR, usize::MAX));//将文法开始符和边界符依次压入栈中
+        analyzer_stack.push((self.start_char.clone(), 0));
         loop {
-            let top_char = analyzer_stack.pop().unwrap();//把栈顶符号弹出，放入x
-            if now_char == top_char && top_char == PredictionAnalyzer::BEGIN_END_CHAR {//分析成功
-                pull_down_queue.push("匹配，分析成功".to_string());
-                return Ok(pull_down_queue);
+            let now_char = tokens[position].0;
+            //记录执行动作前的分析栈与剩余输入串
+            let mut record = |action: AnalysisAction, analyzer_stack: &Vec<(char, usize)>| steps.push(AnalysisStep {
+                step: steps.len() + 1,
+                stack: self.symbol_table.show_sequence(analyzer_stack.iter().map(|(x, _)| *x)),
+                remaining: tokens[position..].iter().map(|(_, x, _)| x.as_str()).collect::<Vec<_>>()
+                    .join(if self.symbol_table.is_multi_char() { " " } else { "" }),
+                action,
+            });
+            let (top_char, top_node) = *analyzer_stack.last().unwrap();
+            let target_str = self.analyzer_table.get(&PredictionAnalyzerInput::new(top_char, now_char)).filter(|x| !x.is_empty());
+            if now_char == top_char && top_char == PredictionAnalyzer::BEGIN_END_CHAR {//分析结束
+                record(if errors.is_empty() { AnalysisAction::Accept } else { AnalysisAction::Finish }, &analyzer_stack);
+                break;
             } else if now_char == top_char {//符号匹配，扫描下一个字符
-                now_char = to_parse_iter.next().unwrap();
-                pull_down_queue.push(format!("匹配，弹出栈顶符号{top_char}并且读入下一个输入符号{now_char}"));
-            } else if top_char.is_ascii_uppercase() {//若栈顶为非终结符
-                if let Some(target_str) = self.analyzer_table.get(&PredictionAnalyzerInput::new(top_char, now_char)) {//查表，获取转换的目标串
-                    if target_str == EMPTY_SENTENCE {//若为推出空串，则只弹出非终结符
-                        pull_down_queue.push(format!("弹出栈顶符号{top_char},由于推出空串，故不压栈"));
-                        continue;
-                    } else if target_str == "" {//若在表中不存在，则报错
-                        return err;
-                    }
-                    target_str.chars().rev().for_each(|x| analyzer_stack.push(x));//逆序压栈
-                    pull_down_queue.push(format!("弹出栈顶符号{top_char},将M[{top_char},{now_char}]中{top_char}->{target_str}中的{target_str}逆序压栈"));//进行推导记录
-                } else {
-                    return err;//若在表中不存在，则报错
+                record(AnalysisAction::Match(name(top_char)), &analyzer_stack);
+                analyzer_stack.pop();
+                position += 1;
+                recovering = false;
+                continue;
+            } else if let Some(target_str) = target_str.filter(|_| self.is_non_terminal(top_char)) {//若栈顶为非终结符，查表获取转换的目标串
+                record(AnalysisAction::Derive(name(top_char), self.symbol_table.show_production(target_str)), &analyzer_stack);
+                analyzer_stack.pop();
+                if target_str == EMPTY_SENTENCE {//若为推出空串，则只弹出非终结符
+                    nodes.push((EMPTY_SENTENCE_CHAR, Vec::new()));
+                    let leaf = nodes.len() - 1;
+                    nodes[top_node].1.push(leaf);
+                    continue;
                 }
-            } else {
-                return err;
+                let children = target_str.chars().map(|x| {
+                    nodes.push((x, Vec::new()));
+                    nodes.len() - 1
+                }).collect::<Vec<_>>();
+                children.iter().rev().for_each(|x| analyzer_stack.push((nodes[*x].0, *x)));//逆序压栈
+                nodes[top_node].1 = children;
+                continue;
+            }
+            //出错，若在表中不存在或栈顶终结符不匹配
+            if !recovering {
+                let (top, lookahead) = (name(top_char), &tokens[position].1);
+                let reason = if top_char == PredictionAnalyzer::BEGIN_END_CHAR {
+                    format!("分析栈已空但输入串中仍有{lookahead}")
+                } else if self.is_non_terminal(top_char) {
+                    format!("分析表中M[{top},{lookahead}]为空")
+                } else {
+                    format!("栈顶终结符{top}与输入符号{lookahead}不匹配")
+                };
+                errors.push(SyntaxError {
+                    position: tokens[position].2,
+                    reason,
+                    expected: self.expected_terminals(top_char),
+                });
+            }
+            if !recover {
+                break;
+            }
+            recovering = true;
+            let is_synch = self.synch_set.get(&top_char).is_some_and(|x| x.contains(&now_char));
+            if top_char == PredictionAnalyzer::BEGIN_END_CHAR {//栈已空，跳过多余的输入
+                record(AnalysisAction::Skip(tokens[position].1.clone()), &analyzer_stack);
+                position += 1;
+            } else if !self.is_non_terminal(top_char) {//栈顶终结符不匹配，视为缺少该终结符
+                record(AnalysisAction::Pop(name(top_char)), &analyzer_stack);
+                analyzer_stack.pop();
+            } else if is_synch || now_char == PredictionAnalyzer::BEGIN_END_CHAR {//遇到同步符号，弹出栈顶非终结符
+                record(AnalysisAction::Synch(name(top_char)), &analyzer_stack);
+                analyzer_stack.pop();
+            } else {//否则跳过当前输入符号
+                record(AnalysisAction::Skip(tokens[position].1.clone()), &analyzer_stack);
+                position += 1;
             }
         }
+        (steps, build_parse_tree(&nodes, 0, &self.symbol_table), errors)
     }
 }
 
+fn build_parse_tree(nodes: &Vec<(char, Vec<usize>)>, index: usize, symbol_table: &SymbolTable) -> ParseTree {
+    let (symbol, children) = &nodes[index];
+    ParseTree::new(symbol_table.name(*symbol), children.iter().map(|x| build_parse_tree(nodes, *x, symbol_table)).collect())
+}
+
 impl Display for PredictionAnalyzer {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for (PredictionAnalyzerInput { v_n, v_t }, target) in &self.analyzer_table {
+            let (v_n, v_t, target) = (self.symbol_table.name(*v_n), self.symbol_table.name(*v_t), self.symbol_table.show_production(target));
             if let Err(e) = writeln!(f, "M[{v_n},{v_t}]={v_n}->{target}") {
                 return Err(e);
             }
         }
+        for (v_n, synch) in &self.synch_set {
+            let v_n = self.symbol_table.name(*v_n);
+            for v_t in collect_ordered_vec(synch.iter().map(|x| self.symbol_table.name(*x))) {
+                writeln!(f, "M[{v_n},{v_t}]=synch")?;
+            }
+        }
         Ok(())
     }
 }
 
-pub fn test_sentence_using_prediction_analyzer_cli(pa: &PredictionAnalyzer) {
+pub fn analyze_source_using_prediction_analyzer(pa: &PredictionAnalyzer, lexer: &Lexer, source: &str) {
+    //对整个源程序做词法分析后进行预测分析，出错时以行号与列号报告全部错误
+    let tokens = lexer.tokenize(source);
+    println!("词法分析得到{}个记号", tokens.len());
+    match pa.analyzer_tokens(&tokens) {
+        Ok((_, tree)) => {
+            println!("该源程序是可接受的");
+            println!("语法树如下：");
+            println!("{tree}");
+        }
+        Err(_) => {
+            let (_, _, errors) = pa.analyzer_tokens_with_recovery(&tokens);
+            println!("共发现{}处错误", errors.len());
+            for error in &errors {
+                let (line, column) = line_column(source, error.position());
+                println!("第{line}行第{column}列：{error}");
+            }
+        }
+    }
+}
+
+pub fn test_sentence_using_prediction_analyzer_cli(pa: &PredictionAnalyzer, lexer: &Lexer) {
+    let (mut is_dbg, mut is_dot) = (true, false);//是否输出解析过程与语法树，是否输出DOT格式的语法树
+    let mut use_lexer = false;//是否先进行词法分析，以记号流作为输入
+    let mut format = StepTableFormat::Text;//解析过程的输出格式
     loop {
         let mut buffer = String::new();
-        let mut is_dbg = true;
         stdin().read_line(&mut buffer).expect("读取时发生错误！");
+        if buffer.is_empty() {
+            break;
+        }
         let mut iter = buffer.trim().chars().peekable();
         if let Some(front_char) = iter.peek() {
             if *front_char == '#' {
-                match iter.collect::<String>().as_str() {
+                let iter_text = iter.collect::<String>();
+                match iter_text.as_str() {
                     "#debug" => {
                         is_dbg = true;
                         println!("已切换至输出模式！");
@@ -98,6 +375,22 @@ pub fn test_sentence_using_prediction_analyzer_cli(pa: &PredictionAnalyzer) {
                         is_dbg = false;
                         println!("已关闭输出模式！");
                     }
+                    "#text" | "#markdown" | "#csv" => {
+                        format = match iter_text.as_str() {
+                            "#text" => StepTableFormat::Text,
+                            "#markdown" => StepTableFormat::Markdown,
+                            _ => StepTableFormat::Csv,
+                        };
+                        println!("已切换解析过程的输出格式为{}！", &iter_text[1..]);
+                    }
+                    "#lexer" => {
+                        use_lexer = !use_lexer;
+                        println!("已{}词法分析！", if use_lexer { "开启" } else { "关闭" });
+                    }
+                    "#dot" => {
+                        is_dot = !is_dot;
+                        println!("已{}DOT格式输出！", if is_dot { "开启" } else { "关闭" });
+                    }
                     _ => {
                         println!("未知的指令！")
                     }
@@ -105,16 +398,45 @@ pub fn test_sentence_using_prediction_analyzer_cli(pa: &PredictionAnalyzer) {
                 continue;
             }
         }
-        match pa.analyzer(&buffer.trim().to_string()) {
-            Ok(vec) => {
+        let to_parse = buffer.trim().to_string();
+        let result = if use_lexer { pa.analyzer_tokens(&lexer.tokenize(&to_parse)) } else { pa.analyzer(&to_parse) };
+        match result {
+            Ok((vec, tree)) => {
                 println!("该字符串是可接受的");
                 if is_dbg {
                     println!("解析过程如下：");
-                    vec.into_iter().for_each(|x| println!("{x}"));
+                    println!("{}", render_analysis_steps(&vec, format));
+                    println!("语法树如下：");
+                    println!("{tree}");
+                }
+                if is_dot {
+                    println!("{}", tree.to_dot());
                 }
             }
-            Err(res) => {
-                println!("{res}");
+            Err(_) => {
+                //以恐慌模式重新分析，在每个出错的字符下方标出^
+                let (vec, tree, errors) = if use_lexer {
+                    pa.analyzer_tokens_with_recovery(&lexer.tokenize(&to_parse))
+                } else {
+                    pa.analyzer_with_recovery(&to_parse)
+                };
+                let input = format!("{}{}", buffer.trim(), PredictionAnalyzer::BEGIN_END_CHAR);
+                let mut carets = String::new();
+                for error in &errors {
+                    let prefix = input.chars().take(error.position()).collect::<String>();
+                    carets += &" ".repeat(display_width(&prefix).saturating_sub(display_width(&carets)));
+                    carets.push('^');
+                }
+                println!("{input}");
+                println!("{carets}");
+                println!("共发现{}处错误", errors.len());
+                errors.iter().for_each(|x| println!("{x}"));
+                if is_dbg {
+                    println!("解析过程如下：");
+                    println!("{}", render_analysis_steps(&vec, format));
+                    println!("部分语法树如下：");
+                    println!("{tree}");
+                }
             }
         }
     }
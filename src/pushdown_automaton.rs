@@ -0,0 +1,447 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::io::stdin;
+
+use crate::deterministic_finite_automaton::{AlphaTable, DeterministicFiniteAutomaton, State, StateSet, TransFunc};
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::r#type::StringArgs;
+use crate::statics::{EMPTY_SENTENCE, EMPTY_SENTENCE_CHAR, SPLIT_UNITS};
+use crate::utils::{collect_ordered_vec, fresh_non_terminal};
+
+#[derive(Eq, PartialEq, Clone, Copy, Hash, Debug, Default)]
+pub struct PdaTransFunc {
+    //PDA转换函数的定义
+    now_state: State, //当前状态
+    input_alpha: char, //输入的符号 为EMPTY_SENTENCE_CHAR时表示ε转移
+    stack_top: char, //栈顶符号
+}
+
+impl PdaTransFunc {
+    pub fn new(now_state: State, input_alpha: char, stack_top: char) -> Self {
+        PdaTransFunc {
+            now_state,
+            input_alpha,
+            stack_top,
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Hash, Debug)]
+pub enum AcceptMode {
+    FinalState, //终态接受
+    EmptyStack, //空栈接受
+}
+
+pub type PdaTarget = (State, String);
+//转移的目标：新状态和替换栈顶的串（串首为新栈顶，EMPTY_SENTENCE表示弹栈）
+pub type PdaTriple = (State, char, State);
+//三元组[pXq]
+pub type PdaFunction = HashMap<PdaTransFunc, HashSet<PdaTarget>>;
+
+#[derive(Debug, Clone)]
+pub struct PushdownAutomaton {
+    state: StateSet, //状态集合
+    alpha: AlphaTable, //输入字母表
+    stack_alpha: HashSet<char>, //栈字母表
+    start_state: State, //开始状态
+    start_stack: char, //栈底符号
+    end_state_set: StateSet, //终态集合
+    trans: PdaFunction, //转换函数
+    accept_mode: AcceptMode, //接受方式
+}
+
+#[derive(Eq, PartialEq, Clone, Hash, Debug)]
+pub struct InstantaneousDescription {
+    state: State,
+    rest_input: String,
+    stack: String, //串首为栈顶
+}
+
+impl Display for InstantaneousDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |s: &String| if s.is_empty() { EMPTY_SENTENCE.to_string() } else { s.clone() };
+        write!(f, "({}, {}, {})", self.state, show(&self.rest_input), show(&self.stack))
+    }
+}
+
+impl PushdownAutomaton {
+    #[allow(clippy::too_many_arguments)]
+    pub fn build(
+        state: StateSet,
+        alpha: AlphaTable,
+        stack_alpha: HashSet<char>,
+        start_state: State,
+        start_stack: char,
+        end_state_set: StateSet,
+        trans: PdaFunction,
+        accept_mode: AcceptMode,
+    ) -> Result<Self, String> {
+        if !state.contains(&start_state) || !end_state_set.is_subset(&state) {
+            return Err("开始状态或终态不在状态集合中".to_string());
+        }
+        if !stack_alpha.contains(&start_stack) {
+            return Err(format!("栈底符号{start_stack}不在栈字母表中"));
+        }
+        for (func, targets) in &trans {
+            //判断转换函数中出现的符号是否出现在给出的集合中
+            if !state.contains(&func.now_state) || !stack_alpha.contains(&func.stack_top) {
+                return Err(format!("转换函数{}+{}+{}中的状态或栈符号未定义", func.now_state, func.input_alpha, func.stack_top));
+            }
+            if func.input_alpha != EMPTY_SENTENCE_CHAR && !alpha.contains(&func.input_alpha) {
+                return Err(format!("输入符号{}不在字母表中", func.input_alpha));
+            }
+            for (target, push) in targets {
+                if !state.contains(target) || (push != EMPTY_SENTENCE && push.chars().any(|x| !stack_alpha.contains(&x))) {
+                    return Err(format!("转换目标{target}+{push}中的状态或栈符号未定义"));
+                }
+            }
+        }
+        Ok(Self {
+            state,
+            alpha,
+            stack_alpha,
+            start_state,
+            start_stack,
+            end_state_set,
+            trans,
+            accept_mode,
+        })
+    }
+    pub fn parse_stack_alpha(string: String) -> Result<HashSet<char>, ()> {
+        //栈字母表 如 Z,A,B
+        let mut stack_alpha = HashSet::new();
+        for alpha in string.split(SPLIT_UNITS) {
+            if alpha.chars().count() != 1 {
+                return Err(());
+            }
+            stack_alpha.insert(alpha.chars().next().unwrap());
+        }
+        Ok(stack_alpha)
+    }
+    pub fn parse_trans(trans: String) -> Result<PdaFunction, ()> {
+        //如 A+a+Z=A+aZ,A+$+Z=B+$ 表示 δ(A,a,Z)∋(A,aZ) δ(A,ε,Z)∋(B,ε)
+        let mut function: PdaFunction = HashMap::new();
+        for token in trans.split(SPLIT_UNITS) {
+            let mut whole_pat = token.split(TransFunc::RESULT_CHAT);
+            let (left, right) = (whole_pat.next().ok_or(())?, whole_pat.next().ok_or(())?);
+            let left = left.split(TransFunc::UNIT_CHAR).collect::<Vec<_>>();
+            let right = right.split(TransFunc::UNIT_CHAR).collect::<Vec<_>>();
+            if left.len() != 3 || right.len() != 2 || left.iter().chain(right.iter().take(1)).any(|x| x.chars().count() != 1) || right[1].is_empty() {
+                return Err(());
+            }
+            let single = |s: &str| s.chars().next().unwrap();
+            function.entry(PdaTransFunc::new(single(left[0]).to_ascii_uppercase(), single(left[1]), single(left[2])))
+                .or_default()
+                .insert((single(right[0]).to_ascii_uppercase(), right[1].to_string()));
+        }
+        Ok(function)
+    }
+    pub fn parse_accept_mode(mode: String) -> Result<AcceptMode, ()> {
+        match mode.as_str() {
+            "final" => Ok(AcceptMode::FinalState),
+            "empty" => Ok(AcceptMode::EmptyStack),
+            _ => Err(()),
+        }
+    }
+    fn fresh_start(&self) -> Result<(State, char), String> {
+        //为转换构造新的开始状态和新的栈底符号
+        let new_start = fresh_non_terminal(&self.state).ok_or("无法生成新的状态")?;
+        let new_bottom = fresh_non_terminal(&self.stack_alpha).ok_or("无法生成新的栈底符号")?;
+        Ok((new_start, new_bottom))
+    }
+    pub fn to_empty_stack(&self) -> Result<Self, String> {
+        //终态接受 -> 空栈接受
+        //新开始状态压入原栈底，到达终态后进入清栈状态弹出所有符号，新栈底防止原PDA中途栈空被误接受
+        if self.accept_mode == AcceptMode::EmptyStack {
+            return Ok(self.clone());
+        }
+        let (new_start, new_bottom) = self.fresh_start()?;
+        let mut state = self.state.clone();
+        state.insert(new_start);
+        let clear_state = fresh_non_terminal(&state).ok_or("无法生成新的状态")?;
+        state.insert(clear_state);
+        let mut stack_alpha = self.stack_alpha.clone();
+        stack_alpha.insert(new_bottom);
+        let mut trans = self.trans.clone();
+        trans.entry(PdaTransFunc::new(new_start, EMPTY_SENTENCE_CHAR, new_bottom)).or_default()
+            .insert((self.start_state, format!("{}{new_bottom}", self.start_stack)));
+        for &stack_top in &stack_alpha {
+            for &end_state in &self.end_state_set {
+                trans.entry(PdaTransFunc::new(end_state, EMPTY_SENTENCE_CHAR, stack_top)).or_default()
+                    .insert((clear_state, EMPTY_SENTENCE.to_string()));
+            }
+            trans.entry(PdaTransFunc::new(clear_state, EMPTY_SENTENCE_CHAR, stack_top)).or_default()
+                .insert((clear_state, EMPTY_SENTENCE.to_string()));
+        }
+        PushdownAutomaton::build(state, self.alpha.clone(), stack_alpha, new_start, new_bottom, HashSet::new(), trans, AcceptMode::EmptyStack)
+    }
+    pub fn to_final_state(&self) -> Result<Self, String> {
+        //空栈接受 -> 终态接受
+        //新开始状态压入原栈底，任何状态下看到新栈底说明原PDA已栈空，转入新终态
+        if self.accept_mode == AcceptMode::FinalState {
+            return Ok(self.clone());
+        }
+        let (new_start, new_bottom) = self.fresh_start()?;
+        let mut state = self.state.clone();
+        state.insert(new_start);
+        let final_state = fresh_non_terminal(&state).ok_or("无法生成新的状态")?;
+        let mut stack_alpha = self.stack_alpha.clone();
+        stack_alpha.insert(new_bottom);
+        let mut trans = self.trans.clone();
+        trans.entry(PdaTransFunc::new(new_start, EMPTY_SENTENCE_CHAR, new_bottom)).or_default()
+            .insert((self.start_state, format!("{}{new_bottom}", self.start_stack)));
+        for &now_state in &self.state {
+            trans.entry(PdaTransFunc::new(now_state, EMPTY_SENTENCE_CHAR, new_bottom)).or_default()
+                .insert((final_state, EMPTY_SENTENCE.to_string()));
+        }
+        state.insert(final_state);
+        PushdownAutomaton::build(state, self.alpha.clone(), stack_alpha, new_start, new_bottom, HashSet::from([final_state]), trans, AcceptMode::FinalState)
+    }
+    pub fn to_grammar(&self) -> Result<(PushDownAutomatonGrammar, Vec<(PdaTriple, char)>), String> {
+        //三元组构造：非终结符[pXq]表示从状态p出发弹出栈顶X后到达状态q所能读入的串
+        //S->[q0 Z0 p] 对每个状态p
+        //δ(q,a,X)∋(r,Y1...Yk) 则 [q X rk]->a[r Y1 r1][r1 Y2 r2]...[rk-1 Yk rk] 对所有 r1...rk
+        let pda = self.to_empty_stack()?;
+        let states = collect_ordered_vec(pda.state.iter().copied());
+        let start = PushDownAutomatonGrammar::START_CHAR;
+        let mut used = HashSet::from([start]);
+        used.extend(pda.alpha.iter().copied());
+        let mut triple_name: HashMap<PdaTriple, char> = HashMap::new();
+        let mut name_of = |triple: PdaTriple| -> Result<char, String> {
+            if let Some(name) = triple_name.get(&triple) {
+                return Ok(*name);
+            }
+            let name = fresh_non_terminal(&used).ok_or("三元组数量过多，无法命名")?;
+            used.insert(name);
+            triple_name.insert(triple, name);
+            Ok(name)
+        };
+        let mut production_set: HashMap<char, HashSet<String>> = HashMap::new();
+        for &end in &states {
+            let name = name_of((pda.start_state, pda.start_stack, end))?;
+            production_set.entry(start).or_default().insert(name.to_string());
+        }
+        for (func, targets) in collect_ordered_vec(pda.trans.iter().map(|(func, targets)| ((func.now_state, func.input_alpha, func.stack_top), collect_ordered_vec(targets.iter().cloned())))) {
+            let (now_state, input_alpha, stack_top) = func;
+            let head = if input_alpha == EMPTY_SENTENCE_CHAR { String::new() } else { input_alpha.to_string() };
+            for (target, push) in targets {
+                let push = if push == EMPTY_SENTENCE { Vec::new() } else { push.chars().collect::<Vec<_>>() };
+                if push.is_empty() {
+                    let name = name_of((now_state, stack_top, target))?;
+                    production_set.entry(name).or_default().insert(if head.is_empty() { EMPTY_SENTENCE.to_string() } else { head.clone() });
+                    continue;
+                }
+                //枚举中间状态序列 r1...rk
+                let mut sequences: Vec<Vec<State>> = vec![Vec::new()];
+                for _ in 0..push.len() {
+                    sequences = sequences.into_iter()
+                        .flat_map(|seq| states.iter().map(move |r| [seq.clone(), vec![*r]].concat()))
+                        .collect();
+                }
+                for seq in sequences {
+                    let mut body = head.clone();
+                    let mut from = target;
+                    for (stack_char, to) in push.iter().zip(seq.iter()) {
+                        body.push(name_of((from, *stack_char, *to))?);
+                        from = *to;
+                    }
+                    let name = name_of((now_state, stack_top, from))?;
+                    production_set.entry(name).or_default().insert(body);
+                }
+            }
+        }
+        let non_terminal = triple_name.values().copied().chain([start]).collect::<HashSet<_>>();
+        let mut mapping = triple_name.into_iter().collect::<Vec<_>>();
+        mapping.sort();
+        Ok((PushDownAutomatonGrammar::new(pda.alpha.clone(), non_terminal, production_set, start), mapping))
+    }
+    pub fn accept_mode(&self) -> AcceptMode {
+        self.accept_mode
+    }
+    fn is_accepted(&self, id: &InstantaneousDescription) -> bool {
+        id.rest_input.is_empty() && match self.accept_mode {
+            AcceptMode::FinalState => self.end_state_set.contains(&id.state),
+            AcceptMode::EmptyStack => id.stack.is_empty(),
+        }
+    }
+    fn next_descriptions(&self, id: &InstantaneousDescription) -> Vec<InstantaneousDescription> {
+        //求一步可达的所有瞬时描述
+        let mut next = Vec::new();
+        let mut stack_iter = id.stack.chars();
+        let top = match stack_iter.next() {
+            Some(top) => top,
+            None => return next,//栈空时无法继续移动
+        };
+        let rest_stack = stack_iter.as_str();
+        let mut input_choices = vec![(EMPTY_SENTENCE_CHAR, id.rest_input.as_str())];
+        if let Some(now_char) = id.rest_input.chars().next() {
+            input_choices.push((now_char, &id.rest_input[now_char.len_utf8()..]));
+        }
+        for (input_alpha, rest_input) in input_choices {
+            if let Some(targets) = self.trans.get(&PdaTransFunc::new(id.state, input_alpha, top)) {
+                for (target, push) in collect_ordered_vec(targets.iter().cloned()) {
+                    let push = if push == EMPTY_SENTENCE { String::new() } else { push };
+                    next.push(InstantaneousDescription {
+                        state: target,
+                        rest_input: rest_input.to_string(),
+                        stack: push + rest_stack,
+                    });
+                }
+            }
+        }
+        next
+    }
+    pub fn simulate(&self, input: &str) -> Result<Option<Vec<InstantaneousDescription>>, String> {
+        //广度优先搜索所有的非确定计算，找到一条最短的接受计算
+        let start = InstantaneousDescription {
+            state: self.start_state,
+            rest_input: input.to_string(),
+            stack: self.start_stack.to_string(),
+        };
+        let mut nodes = vec![(start.clone(), None)];//瞬时描述与其前驱的下标
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([0usize]);
+        while let Some(index) = queue.pop_front() {
+            if self.is_accepted(&nodes[index].0) {
+                let mut path = Vec::new();
+                let mut now = Some(index);
+                while let Some(i) = now {
+                    path.push(nodes[i].0.clone());
+                    now = nodes[i].1;
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+            for next in self.next_descriptions(&nodes[index].0) {
+                if visited.contains(&next) {
+                    continue;
+                }
+                if visited.len() >= PushdownAutomaton::MAX_DESCRIPTIONS {
+                    return Err(format!("搜索的瞬时描述超过{}个，该PDA可能存在无限增长栈的ε转移", PushdownAutomaton::MAX_DESCRIPTIONS));
+                }
+                visited.insert(next.clone());
+                nodes.push((next, Some(index)));
+                queue.push_back(nodes.len() - 1);
+            }
+        }
+        Ok(None)
+    }
+}
+
+pub fn build_pda_with_command_args(mut args: StringArgs) -> PushdownAutomaton {
+    //读取参数并且构造对应的PDA
+    let (mut alpha, mut stack_alpha, mut state_set, mut start_state, mut start_stack, mut end_state_set, mut trans, mut accept_mode) = (
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        AcceptMode::FinalState,
+    );
+    while let Some(mode) = args.next() {
+        if let Some(value) = args.next() {
+            match mode.as_str() {
+                "--alpha" => {
+                    alpha = DeterministicFiniteAutomaton::parse_alpha_table(value)
+                        .expect("字母表解析失败，请检查参数");
+                }
+                "--stack_alpha" => {
+                    stack_alpha = PushdownAutomaton::parse_stack_alpha(value)
+                        .expect("栈字母表解析失败，请检查参数");
+                }
+                "--set" => {
+                    let res = DeterministicFiniteAutomaton::parse_state_set(value)
+                        .expect("状态集解析失败，请检查参数");
+                    state_set = res.0;
+                    end_state_set = res.1;
+                }
+                "--start" => {
+                    start_state = DeterministicFiniteAutomaton::parse_start_state(value)
+                        .expect("初始状态解析失败，请检查参数");
+                }
+                "--stack_start" => {
+                    start_stack = value.chars().next().expect("栈底符号解析失败，请检查参数");
+                }
+                "--trans" => {
+                    trans = PushdownAutomaton::parse_trans(value)
+                        .expect("状态转移函数解析失败，请检查参数");
+                }
+                "--accept" => {
+                    accept_mode = PushdownAutomaton::parse_accept_mode(value)
+                        .expect("接受方式只能为final或empty");
+                }
+                _ => {
+                    println!("未知的子命令！")
+                }
+            }
+        } else {
+            panic!("excepted value of param {mode}");
+        }
+    }
+    PushdownAutomaton::build(state_set, alpha, stack_alpha, start_state, start_stack, end_state_set, trans, accept_mode)
+        .expect("创建PDA失败，请检查参数是否合法！")
+}
+
+pub fn simulate_pda_in_the_terminal(pda: PushdownAutomaton) {
+    println!("pda loaded");
+    loop {
+        let mut buffer = String::new();
+        stdin().read_line(&mut buffer).expect("读取时发生错误！");
+        if buffer.is_empty() {
+            break;
+        }
+        let sentence = buffer.trim();
+        let sentence = if sentence == EMPTY_SENTENCE { "" } else { sentence };
+        match pda.simulate(sentence) {
+            Ok(Some(computation)) => {
+                println!("该字符串是可接受的，接受计算如下");
+                println!("{}", computation.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ⊢ "));
+            }
+            Ok(None) => {
+                println!("该字符串是不可接受的");
+            }
+            Err(e) => {
+                println!("{e}");
+            }
+        }
+    }
+}
+
+impl Display for PushdownAutomaton {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut trans_fmt = String::new();
+        for (func, targets) in collect_ordered_vec(self.trans.iter().map(|(func, targets)| ((func.now_state, func.input_alpha, func.stack_top), collect_ordered_vec(targets.iter().cloned())))) {
+            for (target, push) in targets {
+                trans_fmt += &format!("    δ({},{},{})∋({target},{push})\n", func.0, func.1, func.2);
+            }
+        }
+        write!(
+            f,
+            "
+    This is a PDA
+    states is {:?}
+    alphas is {:?}
+    stack alphas is {:?}
+    start state is {:?}
+    start stack is {:?}
+    end_state_set is {:?}
+    accept by {}
+    trans :
+{}
+      ",
+            collect_ordered_vec(self.state.iter().copied()),
+            collect_ordered_vec(self.alpha.iter().copied()),
+            collect_ordered_vec(self.stack_alpha.iter().copied()),
+            self.start_state,
+            self.start_stack,
+            collect_ordered_vec(self.end_state_set.iter().copied()),
+            match self.accept_mode {
+                AcceptMode::FinalState => "final state",
+                AcceptMode::EmptyStack => "empty stack",
+            },
+            trans_fmt
+        )
+    }
+}
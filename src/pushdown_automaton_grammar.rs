@@ -1,24 +1,143 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 
 use crate::deterministic_finite_automaton::State;
+use crate::grammar_file::load_grammar_file;
+use crate::grammar_symbol::{GrammarSymbol, SymbolTable};
 use crate::prediction_analyzer::{PredictionAnalyzer, PredictionAnalyzerInput};
+use crate::pushdown_automaton::{AcceptMode, PdaFunction, PdaTransFunc, PushdownAutomaton};
 use crate::r#type::StringArgs;
-use crate::statics::{EMPTY_SENTENCE, EMPTY_SENTENCE_CHAR, GRAMMAR_SPLIT_TARGET_UNIT, SPLIT_UNITS};
-use crate::utils::split_type_two_grammar;
+use crate::statics::{EMPTY_SENTENCE, EMPTY_SENTENCE_CHAR, GRAMMAR_SPLIT_IO_UNIT, GRAMMAR_SPLIT_TARGET_UNIT, SPLIT_UNITS};
+use crate::utils::{collect_ordered_vec, split_type_two_grammar};
 
-type FirstSet = HashMap<char, HashSet<char>>;
-type FollowSet = HashMap<char, HashSet<char>>;
+pub type FirstSet = HashMap<char, HashSet<char>>;
+pub type FollowSet = HashMap<char, HashSet<char>>;
 type SelectSet = HashMap<(char, String), HashSet<char>>;
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Ll1ConflictKind {
+    FirstFirst, //两个候选式的FIRST集合相交
+    FirstFollow, //可推出空串的候选式的FOLLOW集合与另一候选式的FIRST集合相交
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Ll1Conflict {
+    //各符号均为经符号表转换后的名字
+    v_n: String,
+    productions: (String, String),
+    select_sets: (Vec<String>, Vec<String>),
+    overlap: Vec<String>, //发生冲突的表项M[v_n,a]中的a
+    kind: Ll1ConflictKind,
+    follow: Vec<String>,
+}
+
+impl Ll1Conflict {
+    pub fn kind(&self) -> Ll1ConflictKind {
+        self.kind
+    }
+}
+
+impl Display for Ll1Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |x: &Vec<String>| x.join(",");
+        let v_n = &self.v_n;
+        let ((i, j), (i_select, j_select)) = (&self.productions, &self.select_sets);
+        let cells = self.overlap.iter().map(|x| format!("M[{v_n},{x}]")).collect::<Vec<_>>().join(" ");
+        match self.kind {
+            Ll1ConflictKind::FirstFirst => write!(f, "{cells} 存在FIRST/FIRST冲突")?,
+            Ll1ConflictKind::FirstFollow => write!(f, "{cells} 存在FIRST/FOLLOW冲突 FOLLOW({v_n})={{{}}}", show(&self.follow))?,
+        }
+        write!(f, " : SELECT({v_n}->{i})={{{}}} SELECT({v_n}->{j})={{{}}} 交集为{{{}}}", show(i_select), show(j_select), show(&self.overlap))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PushDownAutomatonGrammar {
     terminal: HashSet<char>,
     non_terminal: HashSet<char>,
     production_set: HashMap<char, HashSet<String>>,
     start: char,
+    symbol_table: SymbolTable, //多字符文法符号的名字，产生式中以编码表示
 }
 
 impl PushDownAutomatonGrammar {
+    pub fn new(terminal: HashSet<char>, non_terminal: HashSet<char>, production_set: HashMap<char, HashSet<String>>, start: char) -> Self {
+        PushDownAutomatonGrammar {
+            terminal,
+            non_terminal,
+            production_set,
+            start,
+            symbol_table: SymbolTable::default(),
+        }
+    }
+    pub fn with_symbol_table(mut self, symbol_table: SymbolTable) -> Self {
+        self.symbol_table = symbol_table;
+        self
+    }
+    pub fn check_single_char_symbols(&self, tool: &str) -> Result<(), String> {
+        //按单个字符显示符号、读入句子的分析器不能处理多字符的文法符号
+        if self.symbol_table.is_multi_char() {
+            return Err(format!("{tool}暂不支持多字符的文法符号，请改用单字符的文法符号"));
+        }
+        Ok(())
+    }
+    pub fn build_with_symbols(grammar_tokens: &str, start: &str) -> Result<PushDownAutomatonGrammar, String> {
+        //产生式中的符号以空格分隔，如 E->T E',E'->+ T E'|$
+        //出现在产生式左部的符号为非终结符，其余为终结符
+        let rules = grammar_tokens.split(SPLIT_UNITS)
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| x.split_once(GRAMMAR_SPLIT_IO_UNIT).map(|(left, right)| (left.trim(), right)).ok_or(format!("{x} 缺少{GRAMMAR_SPLIT_IO_UNIT}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let left_names = rules.iter().map(|(left, _)| *left).collect::<HashSet<_>>();
+        let classify = |name: &str| if left_names.contains(name) {
+            GrammarSymbol::NonTerminal(name.to_string())
+        } else {
+            GrammarSymbol::Terminal(name.to_string())
+        };
+        let mut classified = Vec::new();
+        for (left, right) in &rules {
+            let mut alternatives = Vec::new();
+            for alternative in right.split(GRAMMAR_SPLIT_TARGET_UNIT) {
+                let names = alternative.split_whitespace().collect::<Vec<_>>();
+                if names.is_empty() {
+                    return Err(format!("{left}{GRAMMAR_SPLIT_IO_UNIT}{right} 含有空的候选式，空串请使用{EMPTY_SENTENCE}"));
+                }
+                alternatives.push(names.into_iter().filter(|x| *x != EMPTY_SENTENCE).map(classify).collect());
+            }
+            classified.push((left.to_string(), alternatives));
+        }
+        PushDownAutomatonGrammar::build_with_rules(classified, start)
+    }
+    pub fn build_with_rules(rules: Vec<(String, Vec<Vec<GrammarSymbol>>)>, start: &str) -> Result<PushDownAutomatonGrammar, String> {
+        //符号已明确区分终结符与非终结符，空的候选式表示空串
+        if !rules.iter().any(|(left, _)| left == start) {
+            return Err(format!("开始符号{start}没有任何产生式"));
+        }
+        let mut symbol_table = SymbolTable::default();
+        let start = symbol_table.intern(GrammarSymbol::NonTerminal(start.to_string()))?;
+        let mut grammar = PushDownAutomatonGrammar::new(HashSet::new(), HashSet::from([start]), HashMap::new(), start);
+        for (left, alternatives) in rules {
+            let left_v_n = symbol_table.intern(GrammarSymbol::NonTerminal(left))?;
+            grammar.non_terminal.insert(left_v_n);
+            for alternative in alternatives {
+                let mut production = String::new();
+                for symbol in alternative {
+                    let is_terminal = symbol.is_terminal();
+                    let code = symbol_table.intern(symbol)?;
+                    if is_terminal { grammar.terminal.insert(code) } else { grammar.non_terminal.insert(code) };
+                    production.push(code);
+                }
+                if production.is_empty() {
+                    production = EMPTY_SENTENCE.to_string();
+                }
+                grammar.production_set.entry(left_v_n).or_default().insert(production);
+            }
+        }
+        if let Some(v_n) = grammar.non_terminal.iter().find(|x| !grammar.production_set.contains_key(x)) {
+            return Err(format!("非终结符{}没有任何产生式", symbol_table.name(*v_n)));
+        }
+        Ok(grammar.with_symbol_table(symbol_table))
+    }
     pub fn build_with_case(grammar_tokens: String, start_state: State) -> Result<PushDownAutomatonGrammar, String> {
         if !start_state.is_ascii_uppercase() {
             return Err("不是按照传统约束的合法状态！".to_string());
@@ -28,6 +147,7 @@ impl PushDownAutomatonGrammar {
             non_terminal: Default::default(),
             production_set: Default::default(),
             start: start_state,
+            symbol_table: Default::default(),
         };
         builder.non_terminal.insert(start_state.clone());
         for grammar_sen in grammar_tokens.split(SPLIT_UNITS) {
@@ -50,7 +170,10 @@ impl PushDownAutomatonGrammar {
         }
         Ok(builder)
     }
-    pub fn build_ll1_analyzer(&self) -> Result<(PredictionAnalyzer, FirstSet, FollowSet, SelectSet), String> {
+    fn get_ll1_sets(&self) -> Result<(FirstSet, FollowSet, SelectSet), String> {
+        if let Some(v_n) = self.non_terminal.iter().find(|x| !self.production_set.contains_key(x)) {
+            return Err(format!("非终结符{}没有任何产生式，请先化简文法", self.symbol_table.name(*v_n)));
+        }
         let (mut first_set, mut follow_set) = (HashMap::new(), HashMap::new());
         for &v_n in &self.non_terminal {//递归计算每个非终结符的first_set同时判断是否含有左递归
             if let Err(e) = self.get_first_set(v_n, &mut first_set, &mut HashSet::new()) {
@@ -63,15 +186,14 @@ impl PushDownAutomatonGrammar {
         }
         //计算select集合
         let select_set: SelectSet = self.get_select_set(&first_set, &follow_set);
+        Ok((first_set, follow_set, select_set))
+    }
+    pub fn build_ll1_analyzer(&self) -> Result<(PredictionAnalyzer, FirstSet, FollowSet, SelectSet), String> {
+        let (first_set, follow_set, select_set) = self.get_ll1_sets()?;
         //判断select集合有无交集
-        for left_v_n in &self.non_terminal {
-            for (index, i_production) in self.production_set[left_v_n].iter().enumerate() {
-                for j_production in self.production_set[left_v_n].iter().skip(index + 1) {
-                    if select_set[&(*left_v_n, i_production.clone())].iter().position(|x| select_set[&(*left_v_n, j_production.clone())].contains(x)) != None {
-                        return Err(format!("select 有交集 for {left_v_n}->{i_production} & {left_v_n}->{j_production}"));
-                    }
-                }
-            }
+        let conflicts = self.collect_ll1_conflicts(&first_set, &follow_set, &select_set);
+        if !conflicts.is_empty() {
+            return Err(conflicts.iter().map(|x| x.to_string()).collect::<Vec<_>>().join("\n"));
         }
 
         let mut analyzer_table: HashMap<PredictionAnalyzerInput, String> = HashMap::new();
@@ -82,15 +204,94 @@ impl PushDownAutomatonGrammar {
                 }
             }
         }
-        Ok((PredictionAnalyzer::new(analyzer_table, self.start), first_set, follow_set, select_set))
+        Ok((PredictionAnalyzer::new(analyzer_table, self.start).with_symbol_table(self.symbol_table.clone()), first_set, follow_set, select_set))
+    }
+    pub fn get_ll1_conflicts(&self) -> Result<Vec<Ll1Conflict>, String> {
+        //列出全部SELECT集合相交的候选式对，含左递归等无法计算SELECT集合时返回Err
+        let (first_set, follow_set, select_set) = self.get_ll1_sets()?;
+        Ok(self.collect_ll1_conflicts(&first_set, &follow_set, &select_set))
+    }
+    fn collect_ll1_conflicts(&self, first_set: &FirstSet, follow_set: &FollowSet, select_set: &SelectSet) -> Vec<Ll1Conflict> {
+        //交集中的终结符若同时属于两个候选式的FIRST集合则为FIRST/FIRST冲突，否则来自FOLLOW集合，为FIRST/FOLLOW冲突
+        let mut conflicts = Vec::new();
+        for left_v_n in self.ordered_non_terminal() {
+            let productions = collect_ordered_vec(self.production_set.get(&left_v_n).into_iter().flatten().cloned());
+            let first_of = |x: &String| self.first_of_sequence(&x.chars().collect::<Vec<_>>(), first_set);
+            for (index, i_production) in productions.iter().enumerate() {
+                for j_production in productions.iter().skip(index + 1) {
+                    let i_select = &select_set[&(left_v_n, i_production.clone())];
+                    let j_select = &select_set[&(left_v_n, j_production.clone())];
+                    let (i_first, j_first) = (first_of(i_production), first_of(j_production));
+                    let (first_first, first_follow): (Vec<char>, Vec<char>) = collect_ordered_vec(i_select.intersection(j_select).copied())
+                        .into_iter()
+                        .partition(|x| i_first.contains(x) && j_first.contains(x));
+                    let names = |x: &mut dyn Iterator<Item=char>| collect_ordered_vec(x.map(|x| self.symbol_table.name(x)));
+                    for (kind, overlap) in [(Ll1ConflictKind::FirstFirst, first_first), (Ll1ConflictKind::FirstFollow, first_follow)] {
+                        if overlap.is_empty() {
+                            continue;
+                        }
+                        conflicts.push(Ll1Conflict {
+                            v_n: self.symbol_table.name(left_v_n),
+                            productions: (self.symbol_table.show_production(i_production), self.symbol_table.show_production(j_production)),
+                            select_sets: (names(&mut i_select.iter().copied()), names(&mut j_select.iter().copied())),
+                            overlap: names(&mut overlap.into_iter()),
+                            kind,
+                            follow: names(&mut follow_set.get(&left_v_n).into_iter().flatten().copied()),
+                        });
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+    pub fn to_pda(&self) -> Result<PushdownAutomaton, String> {
+        //构造只有一个状态、以空栈接受的PDA
+        //δ(Q,ε,A)∋(Q,α) 对每个产生式A->α；δ(Q,a,a)∋(Q,ε) 对每个终结符a
+        self.check_single_char_symbols("文法转换为PDA")?;
+        let state = PushDownAutomatonGrammar::PDA_STATE;
+        let mut trans: PdaFunction = HashMap::new();
+        for (left_v_n, production_set) in &self.production_set {
+            for production in production_set {
+                trans.entry(PdaTransFunc::new(state, EMPTY_SENTENCE_CHAR, *left_v_n)).or_default().insert((state, production.clone()));
+            }
+        }
+        for &v_t in &self.terminal {
+            trans.entry(PdaTransFunc::new(state, v_t, v_t)).or_default().insert((state, EMPTY_SENTENCE.to_string()));
+        }
+        Ok(PushdownAutomaton::build(
+            HashSet::from([state]),
+            self.terminal.clone(),
+            self.terminal.union(&self.non_terminal).copied().collect(),
+            state,
+            self.start,
+            HashSet::new(),
+            trans,
+            AcceptMode::EmptyStack,
+        ).expect("由文法构造的PDA必然合法"))
+    }
+    pub fn terminal(&self) -> &HashSet<char> {
+        &self.terminal
+    }
+    pub fn non_terminal(&self) -> &HashSet<char> {
+        &self.non_terminal
+    }
+    pub fn production_set(&self) -> &HashMap<char, HashSet<String>> {
+        &self.production_set
+    }
+    pub fn start(&self) -> char {
+        self.start
+    }
+    pub fn symbol_table(&self) -> &SymbolTable {
+        &self.symbol_table
     }
     fn get_first_set(&self, v_n: char, mem: &mut FirstSet, search_stack: &mut HashSet<char>) -> Result<(), String> {
+        //先检查左递归，否则先处理了非递归的候选式时会因FIRST集合非空而提前返回
+        if search_stack.contains(&v_n) {
+            return Err(format!("在计算非终结符{} 时发现左递归 递归集合如下 : {}", self.symbol_table.name(v_n), self.symbol_table.show_set(search_stack)));//含左递归
+        }
         if mem.contains_key(&v_n) && !mem[&v_n].is_empty() {
             return Ok(());
         }
-        if search_stack.contains(&v_n) {
-            return Err(format!("在计算非终结符{v_n} 时发现左递归 递归集合如下 : {:?}", search_stack));//含左递归
-        }
         search_stack.insert(v_n.clone());
         for production in &self.production_set[&v_n] {
             if production == EMPTY_SENTENCE {//如果直接推出空串
@@ -116,14 +317,54 @@ impl PushDownAutomatonGrammar {
                     }
                     //否则继续计算
                 } else { return Err("未知的字符".to_string()); }
-                if !ended {//如果可以推出空串
-                    mem.entry(v_n.clone()).or_default().insert(EMPTY_SENTENCE_CHAR);//均有空产生式 则加入空串
-                }
+            }
+            if !ended {//如果可以推出空串
+                mem.entry(v_n.clone()).or_default().insert(EMPTY_SENTENCE_CHAR);//均有空产生式 则加入空串
             }
         }
         search_stack.remove(&v_n);
         return Ok(());
     }
+    pub fn first_of_sequence(&self, symbols: &[char], first_set: &FirstSet) -> HashSet<char> {
+        //计算符号串的FIRST集合，整个符号串可推出空串时含空串
+        let mut first = HashSet::new();
+        for symbol in symbols.iter().filter(|x| **x != EMPTY_SENTENCE_CHAR) {
+            if self.terminal.contains(symbol) {
+                first.insert(*symbol);
+                return first;
+            }
+            let symbol_first = first_set.get(symbol).cloned().unwrap_or_default();
+            first.extend(symbol_first.iter().copied().filter(|x| *x != EMPTY_SENTENCE_CHAR));
+            if !symbol_first.contains(&EMPTY_SENTENCE_CHAR) {
+                return first;
+            }
+        }
+        first.insert(EMPTY_SENTENCE_CHAR);
+        first
+    }
+    pub fn get_first_and_follow_set(&self) -> Result<(FirstSet, FollowSet), String> {
+        //以不动点迭代计算FIRST集合，不要求文法无左递归，供LR分析使用
+        let mut first_set: FirstSet = self.non_terminal.iter().map(|x| (*x, HashSet::new())).collect();
+        loop {
+            let mut closed = true;
+            for (left_v_n, production_set) in &self.production_set {
+                for production in production_set {
+                    let first = self.first_of_sequence(&production.chars().collect::<Vec<_>>(), &first_set);
+                    let left_first = first_set.entry(*left_v_n).or_default();
+                    if !first.is_subset(left_first) {
+                        left_first.extend(first);
+                        closed = false;
+                    }
+                }
+            }
+            if closed {
+                break;
+            }
+        }
+        let mut follow_set: FollowSet = self.non_terminal.iter().map(|x| (*x, HashSet::new())).collect();
+        self.get_follow_set(&mut follow_set, &first_set)?;
+        Ok((first_set, follow_set))
+    }
     fn get_follow_set(&self, follow_set: &mut FollowSet, first_set: &FirstSet) -> Result<(), String> {
         follow_set.insert(self.start, HashSet::from([PredictionAnalyzer::BEGIN_END_CHAR]));//FOLLOW(START)=#
         loop {
@@ -151,7 +392,9 @@ impl PushDownAutomatonGrammar {
                                     closed = false;
                                 }
                             } else {
-                                let first_beta_set = first_set[&next_char].clone().into_iter().filter(|x| *x != EMPTY_SENTENCE_CHAR).collect::<HashSet<_>>();
+                                //β以非终结符开头时需计算整个β的FIRST集合
+                                let first_beta_with_empty = self.first_of_sequence(&chars[index + 1..], first_set);
+                                let first_beta_set = first_beta_with_empty.iter().copied().filter(|x| *x != EMPTY_SENTENCE_CHAR).collect::<HashSet<_>>();
                                 let follow_b_set = follow_set.entry(*char).or_default();
                                 if !first_beta_set.is_subset(follow_b_set) {
                                     //将所有非空元素加入
@@ -159,7 +402,7 @@ impl PushDownAutomatonGrammar {
                                     closed = false;
                                 }
 
-                                if first_set[&next_char].contains(&EMPTY_SENTENCE_CHAR) {//如果可以推出空串
+                                if first_beta_with_empty.contains(&EMPTY_SENTENCE_CHAR) {//如果可以推出空串
                                     //则加入follow(A)
                                     let follow_a_set = follow_set.entry(*left_v_n).or_default().clone();
                                     let follow_b_set = follow_set.entry(*char).or_default();
@@ -195,7 +438,7 @@ impl PushDownAutomatonGrammar {
                                 okd = true;//不能产生空串
                             } else {
                                 first_set[&ch].clone().into_iter().filter(|x| *x != EMPTY_SENTENCE_CHAR).for_each(|x| { set.insert(x); });//将first集合直接加入
-                                if !first_set.contains_key(&EMPTY_SENTENCE_CHAR) {//如果不能产生空串
+                                if !first_set[&ch].contains(&EMPTY_SENTENCE_CHAR) {//如果不能产生空串
                                     okd = true;
                                 }
                             }
@@ -214,16 +457,45 @@ impl PushDownAutomatonGrammar {
     }
 }
 
+impl Display for PushDownAutomatonGrammar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        //开始符号的产生式排在最前，其余按非终结符排序
+        let mut left_v_ns = collect_ordered_vec(self.production_set.keys().copied());
+        if let Some(index) = left_v_ns.iter().position(|x| *x == self.start) {
+            let start = left_v_ns.remove(index);
+            left_v_ns.insert(0, start);
+        }
+        for left_v_n in left_v_ns {
+            let productions = collect_ordered_vec(self.production_set[&left_v_n].iter().map(|x| self.symbol_table.show_production(x)));
+            if self.symbol_table.is_multi_char() {
+                writeln!(f, "{} {GRAMMAR_SPLIT_IO_UNIT} {}", self.symbol_table.name(left_v_n), productions.join(&format!(" {GRAMMAR_SPLIT_TARGET_UNIT} ")))?;
+            } else {
+                writeln!(f, "{left_v_n}{GRAMMAR_SPLIT_IO_UNIT}{}", productions.join(GRAMMAR_SPLIT_TARGET_UNIT))?;
+            }
+        }
+        Ok(())
+    }
+}
+
 pub fn build_push_down_automaton_grammar_with_args(mut args: StringArgs) -> PushDownAutomatonGrammar {
-    let (mut grammar_str, mut start_v_n) = (Default::default(), Default::default());
+    let (mut grammar_str, mut start_v_n, mut grammar_file): (String, String, Option<String>) = Default::default();
+    let mut symbols = false;
     while let Some(mode) = args.next() {
+        if mode == "--symbols" {
+            //不带参数值，表示产生式中的符号以空格分隔
+            symbols = true;
+            continue;
+        }
         if let Some(val) = args.next() {
             match mode.as_str() {
                 "--grammar" => {
                     grammar_str = val;
                 }
                 "--start" => {
-                    start_v_n = val.chars().nth(0).unwrap();
+                    start_v_n = val;
+                }
+                "--grammar-file" => {
+                    grammar_file = Some(val);
                 }
                 _ => {}
             }
@@ -231,5 +503,17 @@ pub fn build_push_down_automaton_grammar_with_args(mut args: StringArgs) -> Push
             panic!("excepted value of param  {mode}");
         }
     }
-    PushDownAutomatonGrammar::build_with_case(grammar_str, start_v_n).expect("构建II型文法时发生错误")
+    //从BNF/EBNF文法文件读取时，未给出开始符号则以第一条规则的左部为开始符号
+    if let Some(path) = grammar_file {
+        let start = Some(start_v_n.trim()).filter(|x| !x.is_empty());
+        return load_grammar_file(&path, start).expect("读取文法文件时发生错误");
+    }
+    //给出--symbols时按空格分隔的多字符符号解析，否则每个字符是一个符号，此时不允许出现空白以免与前者混淆
+    if symbols {
+        PushDownAutomatonGrammar::build_with_symbols(&grammar_str, start_v_n.trim()).expect("构建II型文法时发生错误")
+    } else if grammar_str.contains(char::is_whitespace) {
+        panic!("文法 {grammar_str} 中含有空白字符，若要使用空格分隔的多字符符号请加上 --symbols");
+    } else {
+        PushDownAutomatonGrammar::build_with_case(grammar_str, start_v_n.chars().next().unwrap()).expect("构建II型文法时发生错误")
+    }
 }
@@ -80,7 +80,12 @@ impl RegularGrammar {
     }
 }
 
-pub fn build_rg_with_args(mut args: StringArgs) -> RegularGrammar {
+pub fn build_rg_with_args(args: StringArgs) -> RegularGrammar {
+    let (grammar_str, start_char) = read_grammar_args(args);
+    RegularGrammar::parse_grammar_token_and_build(grammar_str, start_char).expect("在构建正规文法时解析失败")
+}
+
+pub fn read_grammar_args(mut args: StringArgs) -> (String, char) {
     let (mut grammar_str, mut start_char) = (Default::default(), Default::default());
     while let Some(mode) = args.next() {
         if let Some(val) = args.next() {
@@ -89,7 +94,7 @@ pub fn build_rg_with_args(mut args: StringArgs) -> RegularGrammar {
                     grammar_str = val;
                 }
                 "--start" => {
-                    start_char = val.chars().next().unwrap();
+                    start_char = val.chars().next().unwrap_or_else(|| panic!("excepted value of param {mode}"));
                 }
                 _ => {}
             }
@@ -97,5 +102,5 @@ pub fn build_rg_with_args(mut args: StringArgs) -> RegularGrammar {
             panic!("excepted value of param {mode}");
         }
     }
-    RegularGrammar::parse_grammar_token_and_build(grammar_str, start_char).expect("在构建正规文法时解析失败")
+    (grammar_str, start_char)
 }
\ No newline at end of file
@@ -0,0 +1,159 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::earley_parser::EarleyParser;
+use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::statics::EMPTY_SENTENCE;
+
+#[derive(Debug, Clone)]
+pub struct XorShiftRandom {
+    state: u64,
+}
+
+impl XorShiftRandom {
+    pub fn new(seed: u64) -> Self {
+        //打散较小的种子，并保证状态非0，否则xorshift会一直输出0
+        XorShiftRandom { state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+    pub fn from_time() -> Self {
+        XorShiftRandom::new(SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_nanos() as u64).unwrap_or(1))
+    }
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state % bound as u64) as usize
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Derivations {
+    leftmost: Vec<String>,
+    rightmost: Vec<String>,
+    ambiguous: bool, //该句子是否有多棵语法树，此时推导只对应其中一棵
+}
+
+impl Derivations {
+    pub fn leftmost(&self) -> &[String] {
+        &self.leftmost
+    }
+    pub fn rightmost(&self) -> &[String] {
+        &self.rightmost
+    }
+    pub fn is_ambiguous(&self) -> bool {
+        self.ambiguous
+    }
+}
+
+impl PushDownAutomatonGrammar {
+    pub fn enumerate_sentences(&self, max_length: usize, limit: usize) -> Result<Vec<String>, String> {
+        //消除空产生式与单产生式后，除S->$外每步推导都不会使句型变短，因此长度超过max_length的句型可以直接剪去
+        //句型按长度分桶，逐个长度按最左推导展开，展开完长度为length的句型后该长度的句子已全部得到，凑够limit个即停止
+        let normalized = self.eliminate_empty_production()?.eliminate_unit_production();
+        let productions = normalized.symbol_productions();
+        let mut buckets = vec![Vec::new(); max_length.max(1) + 1];
+        buckets[1].push(vec![normalized.start()]);
+        let mut visited = HashSet::from([vec![normalized.start()]]);
+        let mut sentences = Vec::new();
+        for length in 1..buckets.len() {
+            let mut queue = VecDeque::from(std::mem::take(&mut buckets[length]));
+            let mut level = Vec::new();
+            while let Some(form) = queue.pop_front() {
+                let index = match form.iter().position(|x| normalized.non_terminal().contains(x)) {
+                    Some(index) => index,
+                    None => {
+                        level.push((form.len(), self.symbol_table().show_sequence(form)));
+                        continue;
+                    }
+                };
+                for body in productions.get(&form[index]).into_iter().flatten() {
+                    let next = [&form[..index], body, &form[index + 1..]].concat();
+                    if next.len() > max_length || !visited.insert(next.clone()) {
+                        continue;
+                    }
+                    //只有S->$会使句型变短，得到的空句子放在本层一并排序
+                    if next.len() <= length {
+                        queue.push_back(next);
+                    } else {
+                        buckets[next.len()].push(next);
+                    }
+                }
+            }
+            level.sort();
+            sentences.extend(level.into_iter().map(|(_, x)| x));
+            if sentences.len() >= limit {
+                break;
+            }
+        }
+        sentences.truncate(limit);
+        Ok(sentences)
+    }
+    fn get_min_height(&self) -> HashMap<char, usize> {
+        //非终结符推导出终结符串所需语法树的最小高度，不能推导出终结符串的非终结符不在结果中
+        let productions = self.symbol_productions();
+        let mut min_height: HashMap<char, usize> = HashMap::new();
+        loop {
+            let mut closed = true;
+            for (left_v_n, bodies) in &productions {
+                let height = bodies.iter().filter_map(|x| self.body_height(x, &min_height)).min();
+                if let Some(height) = height.filter(|x| min_height.get(left_v_n).is_none_or(|y| x < y)) {
+                    min_height.insert(*left_v_n, height);
+                    closed = false;
+                }
+            }
+            if closed {
+                break;
+            }
+        }
+        min_height
+    }
+    fn body_height(&self, body: &[char], min_height: &HashMap<char, usize>) -> Option<usize> {
+        body.iter()
+            .filter(|x| self.non_terminal().contains(x))
+            .map(|x| min_height.get(x).copied())
+            .try_fold(0, |height, x| x.map(|x| height.max(x)))
+            .map(|x| x + 1)
+    }
+    pub fn generate_random_sentence(&self, max_depth: usize, random: &mut XorShiftRandom) -> Result<String, String> {
+        //深度未超过max_depth时在能够及时结束的候选式中随机选择，否则选择高度最小的候选式以尽快结束推导
+        let min_height = self.get_min_height();
+        if !min_height.contains_key(&self.start()) {
+            return Err("该文法不能推导出任何句子".to_string());
+        }
+        let productions = self.symbol_productions();
+        let mut sentence = Vec::new();
+        let mut stack = vec![(self.start(), 0)];
+        while let Some((symbol, depth)) = stack.pop() {
+            if !self.non_terminal().contains(&symbol) {
+                sentence.push(symbol);
+                continue;
+            }
+            let bodies = productions[&symbol].iter()
+                .filter_map(|x| self.body_height(x, &min_height).map(|height| (x, height)))
+                .collect::<Vec<_>>();
+            let mut candidates = bodies.iter().filter(|(_, height)| depth + height <= max_depth).collect::<Vec<_>>();
+            if candidates.is_empty() {
+                let lowest = bodies.iter().map(|(_, height)| *height).min().unwrap();
+                candidates = bodies.iter().filter(|(_, height)| *height == lowest).collect();
+            }
+            let (body, _) = candidates[random.next_below(candidates.len())];
+            stack.extend(body.iter().rev().map(|x| (*x, depth + 1)));
+        }
+        Ok(self.symbol_table().show_sequence(sentence))
+    }
+    pub fn get_derivations(&self, sentence: &str) -> Result<Derivations, String> {
+        //用Earley分析得到语法树，再分别按最左、最右的顺序展开，语法树的结点即为符号的名字
+        let symbols = self.symbol_table();
+        let (_, trees) = EarleyParser::new(self).parse(&symbols.encode(sentence)?, 2);
+        let tree = trees.first().ok_or("该句子不属于该文法所描述的语言")?;
+        let separator = if symbols.is_multi_char() { " " } else { "" };
+        let show = |leftmost: bool| tree.derivation(leftmost).into_iter()
+            .map(|form| if form.is_empty() { EMPTY_SENTENCE.to_string() } else { form.join(separator) })
+            .collect();
+        Ok(Derivations {
+            leftmost: show(true),
+            rightmost: show(false),
+            ambiguous: trees.len() > 1,
+        })
+    }
+}
@@ -1,5 +1,11 @@
+use crate::automaton_learner::LStarLearner;
 use crate::deterministic_finite_automaton::{DeterministicFiniteAutomaton, State, TransFunc};
+use crate::earley_parser::EarleyParser;
+use crate::grammar_symbol::SymbolTable;
+use crate::lexer::Lexer;
+use crate::lr_parser::PrecedenceDeclarations;
 use crate::prediction_analyzer::PredictionAnalyzer;
+use crate::pushdown_automaton::PushdownAutomaton;
 use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
 use crate::regular_grammar::RegularGrammar;
 
@@ -7,7 +13,41 @@ impl RegularGrammar {
     pub const END_STATE: State = '+';
 }
 
-impl PushDownAutomatonGrammar {}
+impl PushDownAutomatonGrammar {
+    pub const PDA_STATE: State = 'Q';
+    pub const START_CHAR: char = 'S';
+    pub const DEFAULT_SENTENCE_LENGTH: usize = 6;
+    pub const DEFAULT_SENTENCE_LIMIT: usize = 50;
+    pub const DEFAULT_DERIVATION_DEPTH: usize = 8;
+}
+
+impl SymbolTable {
+    pub const PRIVATE_USE_BEGIN: u32 = 0xE000;
+    pub const PRIVATE_USE_END: u32 = 0xF8FF;
+}
+
+impl Lexer {
+    pub const IDENTIFIER_KIND: &'static str = "id";
+    pub const NUMBER_KIND: &'static str = "num";
+    pub const KIND_MAP_SPLIT_UNIT: char = ':';
+}
+
+impl PushdownAutomaton {
+    pub const MAX_DESCRIPTIONS: usize = 100000;
+}
+
+impl EarleyParser {
+    pub const MAX_PARSE_TREES: usize = 8;
+}
+
+impl PrecedenceDeclarations {
+    pub const ASSOCIATIVITY_SPLIT_UNIT: char = ':';
+    pub const DECLARATION_SPLIT_UNITS: [char; 2] = [';', '\n'];
+}
+
+impl LStarLearner {
+    pub const DEFAULT_MAX_ROUNDS: usize = 64;
+}
 
 impl PredictionAnalyzer {
     pub const BEGIN_END_CHAR: char = '#';
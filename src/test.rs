@@ -1,8 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use crate::automaton_learner::{ClosureOracle, LStarLearner};
+use crate::chomsky_hierarchy::{ChomskyClassification, ChomskyType, is_deterministic_right_linear, Linearity};
+use crate::cyk_parser::CykParser;
+use crate::deterministic_finite_automaton::{DeterministicFiniteAutomaton, TransFunc};
+use crate::earley_parser::EarleyParser;
+use crate::grammar_file::load_grammar_file;
+use crate::lexer::{line_column, Lexer};
+use crate::lr_automaton::LrAutomaton;
+use crate::lr_parser::{LrParsingTable, PrecedenceDeclarations};
 use crate::main_application::main_application;
-use crate::prediction_analyzer::{PredictionAnalyzer, PredictionAnalyzerInput};
-use crate::pushdown_automaton_grammar::PushDownAutomatonGrammar;
+use crate::operator_precedence::{OperatorPrecedenceParser, PrecedenceRelation};
+use crate::parse_tree::ParseTree;
+use crate::prediction_analyzer::{PredictionAnalyzer, PredictionAnalyzerInput, render_analysis_steps, StepTableFormat};
+use crate::pushdown_automaton::build_pda_with_command_args;
+use crate::pushdown_automaton_grammar::{build_push_down_automaton_grammar_with_args, Ll1ConflictKind, PushDownAutomatonGrammar};
+use crate::regular_grammar::read_grammar_args;
+use crate::sentence_generator::XorShiftRandom;
 use crate::statics::EMPTY_SENTENCE;
 
 #[test]
@@ -30,12 +44,11 @@ fn test_ll1() {
         ('V', '#', EMPTY_SENTENCE),
     ].into_iter().map(|(v_n, v_t, tag)| (PredictionAnalyzerInput::new(v_n, v_t), tag.to_string())).collect::<HashMap<_, _>>();
     let pa = PredictionAnalyzer::new(ll1_table, 'E');
-    match pa.analyzer(&"i+i+(i*i+i)+i*(i+i)+(i)".to_string()) {
-        Ok(res) => {
+    match pa.analyzer("i+i+(i*i+i)+i*(i+i)+(i)") {
+        Ok((res, tree)) => {
             println!("analysis stack is");
-            for str in res {
-                println!("{str}")
-            }
+            println!("{}", render_analysis_steps(&res, StepTableFormat::Text));
+            println!("{tree}");
         }
         Err(err) => {
             println!("{err}");
@@ -63,4 +76,657 @@ fn test_first() {
     let pa = PredictionAnalyzer::new(ll1_table, 'E');
     let pd = PushDownAutomatonGrammar::build_with_case("E->TU,U->+TU|$,T->FV,V->*FV|$,F->(E)|i".to_string(), 'E').expect("err");
     assert_eq!(pa, pd.build_ll1_analyzer().expect("SS").0);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_follow_with_nullable_suffix() {
+    //A后的β=Bc中B可推出空串，FOLLOW(A)应包含FIRST(B)与c，而不是FOLLOW(S)
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->ABc,A->a,B->b|$".to_string(), 'S').expect("err");
+    let (analyzer, _, follow, _) = grammar.build_ll1_analyzer().expect("ll1");
+    assert_eq!(follow[&'A'], HashSet::from(['b', 'c']));
+    assert_eq!(follow[&'B'], HashSet::from(['c']));
+    assert!(analyzer.analyzer("ac").is_ok());
+    assert!(analyzer.analyzer("abc").is_ok());
+}
+
+#[test]
+fn test_first_and_select_with_nullable_prefix() {
+    //S->AB 中A可推出空串：FIRST(S)与SELECT(S->AB)都应包含FIRST(B)，且S本身不能推出空串
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->AB|c,A->a|$,B->b".to_string(), 'S').expect("err");
+    let (analyzer, first, _, select) = grammar.build_ll1_analyzer().expect("ll1");
+    assert_eq!(first[&'S'], HashSet::from(['a', 'b', 'c']));
+    assert_eq!(select[&('S', "AB".to_string())], HashSet::from(['a', 'b']));
+    assert!(analyzer.analyzer("b").is_ok());
+    assert!(analyzer.analyzer("ab").is_ok());
+    assert!(analyzer.analyzer("").is_err());
+}
+
+#[test]
+fn test_lstar_learning() {
+    //目标语言：含偶数个a且以b结尾的串，输入的DFA并非最小
+    let target = DeterministicFiniteAutomaton::build(
+        HashSet::from(['a', 'b']),
+        HashSet::from(['A', 'B', 'C', 'D']),
+        'A',
+        HashSet::from(['B']),
+        [('A', 'a', 'C'), ('A', 'b', 'B'), ('B', 'a', 'C'), ('B', 'b', 'B'), ('C', 'a', 'A'), ('C', 'b', 'D'), ('D', 'a', 'A'), ('D', 'b', 'D')]
+            .into_iter().map(|(from, alpha, to)| (TransFunc::new(from, alpha), to)).collect(),
+    ).expect("build dfa");
+    let (learned, dumps) = LStarLearner::new(target.alpha().clone()).learn(&target).expect("learn");
+    dumps.iter().for_each(|x| println!("{x}"));
+    assert_eq!(target.find_distinguishing_word(&learned), None);
+    assert_eq!(learned.state().len(), 3);
+    let oracle = ClosureOracle::new(|word: &str| word.ends_with("ab"), HashSet::from(['a', 'b']), 6);
+    let (learned, _) = LStarLearner::new(HashSet::from(['a', 'b'])).learn(&oracle).expect("learn");
+    assert!(learned.accept("bbab") && !learned.accept("aba"));
+    //状态数超过26个时不再使用大写字母之后的字符命名
+    let oracle = ClosureOracle::new(|word: &str| word.len().is_multiple_of(30), HashSet::from(['a']), 60);
+    let (learned, _) = LStarLearner::new(HashSet::from(['a'])).learn(&oracle).expect("learn");
+    assert_eq!(learned.state().len(), 30);
+    assert!(learned.state().iter().all(|x| x.is_uppercase()));
+}
+
+#[test]
+fn test_pda_simulation() {
+    //{a^n b^n | n>=1}，分别以终态和空栈接受
+    for (accept, end_states) in [("final", "A,B,*C"), ("empty", "A,B,C")] {
+        let commands = format!("--alpha a,b --stack_alpha Z,X --set {end_states} --start A --stack_start Z --accept {accept} --trans A+a+Z=A+XZ,A+a+X=A+XX,A+b+X=B+$,B+b+X=B+$,B+$+Z=C+Z,C+$+Z=C+$");
+        let pda = build_pda_with_command_args(commands.split(' ').map(String::from).collect::<Vec<_>>().into_iter());
+        let computation = pda.simulate("aabb").expect("simulate").expect("accept");
+        println!("{}", computation.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ⊢ "));
+        assert_eq!(computation.first().unwrap().to_string(), "(A, aabb, Z)");
+        assert_eq!(pda.simulate("aab").expect("simulate"), None);
+        assert_eq!(pda.simulate("").expect("simulate"), None);
+    }
+}
+
+#[test]
+fn test_pda_grammar_conversion() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->aSb|ab".to_string(), 'S').expect("err");
+    let pda = grammar.to_pda().expect("to pda");
+    assert!(pda.simulate("aaabbb").expect("simulate").is_some());
+    assert_eq!(pda.simulate("aabbb").expect("simulate"), None);
+    //以终态接受的PDA -> 空栈接受 -> 三元组文法 -> 单状态PDA，语言保持不变
+    let commands = "--alpha a,b --stack_alpha Z,X --set A,B,*C --start A --stack_start Z --accept final --trans A+a+Z=A+XZ,A+a+X=A+XX,A+b+X=B+$,B+b+X=B+$,B+$+Z=C+Z";
+    let pda = build_pda_with_command_args(commands.split(' ').map(String::from).collect::<Vec<_>>().into_iter());
+    let (grammar, mapping) = pda.to_grammar().expect("to grammar");
+    println!("{grammar}");
+    assert!(!mapping.is_empty());
+    let round_trip = grammar.to_pda().expect("to pda");
+    for (sentence, accepted) in [("ab", true), ("aaabbb", true), ("aab", false), ("ba", false)] {
+        assert_eq!(pda.simulate(sentence).expect("simulate").is_some(), accepted);
+        assert_eq!(pda.to_empty_stack().expect("switch").simulate(sentence).expect("simulate").is_some(), accepted);
+        assert_eq!(round_trip.simulate(sentence).expect("simulate").is_some(), accepted);
+    }
+}
+
+#[test]
+fn test_eliminate_left_recursion() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->E+T|T,T->T*F|F,F->(E)|i".to_string(), 'E').expect("err");
+    assert!(grammar.has_left_recursion());
+    let transformed = grammar.eliminate_left_recursion().expect("eliminate");
+    println!("{transformed}");
+    assert!(!transformed.has_left_recursion());
+    let (analyzer, _, _, _) = transformed.build_ll1_analyzer().expect("ll1");
+    assert!(analyzer.analyzer("i+i*(i+i)").is_ok());
+    assert!(analyzer.analyzer("i+*i").is_err());
+    //间接左递归 S->Aa|b A->Sd|c
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->Aa|b,A->Sd|c".to_string(), 'S').expect("err");
+    assert!(grammar.has_left_recursion());
+    let transformed = grammar.eliminate_left_recursion().expect("eliminate");
+    println!("{transformed}");
+    assert!(!transformed.has_left_recursion());
+    assert_eq!(transformed.production_set()[&'A'], HashSet::from(["bdB".to_string(), "cB".to_string()]));
+    //隐藏在可空前缀之后的左递归，如A->BA中的B可推出空串
+    let grammar = PushDownAutomatonGrammar::build_with_case("A->BA|a,B->$".to_string(), 'A').expect("err");
+    assert!(grammar.has_left_recursion());
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->BSa|b,B->c|$".to_string(), 'S').expect("err");
+    assert!(grammar.has_left_recursion());
+    let transformed = grammar.eliminate_left_recursion().expect("eliminate");
+    println!("{transformed}");
+    assert!(!transformed.has_left_recursion());
+    for (sentence, accepted) in [("b", true), ("ba", true), ("cbaa", true), ("cba", true), ("cb", false), ("a", false)] {
+        assert_eq!(!EarleyParser::new(&transformed).parse(sentence, 1).1.is_empty(), accepted, "{sentence}");
+    }
+}
+
+#[test]
+fn test_left_factor() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->iEtS|iEtSeS|a,E->b".to_string(), 'S').expect("err");
+    assert!(grammar.has_common_prefix());
+    let (factored, report) = grammar.left_factor().expect("factor");
+    report.iter().for_each(|x| println!("{x}"));
+    println!("{factored}");
+    assert!(!factored.has_common_prefix());
+    assert_eq!(factored.production_set()[&'S'], HashSet::from(["a".to_string(), "iEtSA".to_string()]));
+    assert_eq!(factored.production_set()[&'A'], HashSet::from(["$".to_string(), "eS".to_string()]));
+    //提取后仍存在公共左因子时需要继续提取
+    let grammar = PushDownAutomatonGrammar::build_with_case("A->abc|abd|ae|f".to_string(), 'A').expect("err");
+    let (factored, report) = grammar.left_factor().expect("factor");
+    assert_eq!(report.len(), 2);
+    let (analyzer, _, _, _) = factored.build_ll1_analyzer().expect("ll1");
+    for sentence in ["abc", "abd", "ae", "f"] {
+        assert!(analyzer.analyzer(sentence).is_ok());
+    }
+}
+
+#[test]
+fn test_reduce_grammar() {
+    //B无法推导出终结符串，D只能由B到达，E不可达，C没有产生式
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->aA|bB|cC,A->a|bA,B->bB|dDB,D->d,E->e".to_string(), 'S').expect("err");
+    assert!(grammar.build_ll1_analyzer().is_err());
+    let (reduced, report) = grammar.reduce();
+    report.iter().for_each(|x| println!("{x}"));
+    println!("{reduced}");
+    assert_eq!(reduced.non_terminal(), &HashSet::from(['S', 'A']));
+    assert_eq!(reduced.terminal(), &HashSet::from(['a', 'b']));
+    assert_eq!(reduced.production_set()[&'S'], HashSet::from(["aA".to_string()]));
+    assert!(reduced.build_ll1_analyzer().is_ok());
+}
+
+#[test]
+fn test_eliminate_empty_and_unit_production() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->AB|a,A->aA|$,B->bB|$".to_string(), 'S').expect("err");
+    assert_eq!(grammar.get_nullable_set(), HashSet::from(['S', 'A', 'B']));
+    let without_empty = grammar.eliminate_empty_production().expect("eliminate");
+    println!("{without_empty}");
+    //S可空，因此引入新的开始符号C->S|$
+    assert_eq!(without_empty.start(), 'C');
+    assert_eq!(without_empty.production_set()[&'C'], HashSet::from(["S".to_string(), "$".to_string()]));
+    assert_eq!(without_empty.production_set()[&'S'], HashSet::from(["AB", "A", "B", "a"].map(String::from)));
+    assert!(without_empty.production_set().iter().all(|(v_n, set)| *v_n == 'C' || !set.contains("$")));
+    let without_unit = without_empty.eliminate_unit_production();
+    println!("{without_unit}");
+    assert_eq!(without_unit.production_set()[&'C'], HashSet::from(["$", "AB", "aA", "a", "bB", "b"].map(String::from)));
+    assert_eq!(without_unit.production_set()[&'S'], HashSet::from(["AB", "aA", "a", "bB", "b"].map(String::from)));
+}
+
+#[test]
+fn test_cyk_parser() {
+    //非LL(1)的二义文法也可以分析
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->E+E|E*E|(E)|i".to_string(), 'E').expect("err");
+    let cnf = grammar.to_chomsky_normal_form().expect("cnf");
+    println!("{cnf}");
+    assert!(cnf.is_chomsky_normal_form());
+    let parser = CykParser::new(&grammar).expect("cyk");
+    let (table, tree) = parser.parse("i+i*(i+i)");
+    println!("{table}");
+    let tree = tree.expect("accept");
+    println!("{tree}");
+    for (sentence, accepted) in [("i", true), ("(i)*i", true), ("i+", false), ("", false), ("i)(", false)] {
+        assert_eq!(parser.parse(sentence).1.is_some(), accepted);
+    }
+    let parser = CykParser::new(&PushDownAutomatonGrammar::build_with_case("S->aSb|$".to_string(), 'S').expect("err")).expect("cyk");
+    assert!(parser.parse("").1.is_some() && parser.parse("aabb").1.is_some() && parser.parse("abb").1.is_none());
+}
+
+#[test]
+fn test_greibach_normal_form() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->E+T|T,T->T*F|F,F->(E)|i".to_string(), 'E').expect("err");
+    assert!(grammar.check_greibach_normal_form().is_err());
+    let gnf = grammar.to_greibach_normal_form().expect("gnf");
+    println!("{gnf}");
+    assert_eq!(gnf.check_greibach_normal_form(), Ok(()));
+    //在CYK上比较转换前后的语言
+    let (original, transformed) = (CykParser::new(&grammar).expect("cyk"), CykParser::new(&gnf).expect("cyk"));
+    let mut sentences = vec![String::new()];
+    for _ in 0..5 {
+        sentences = sentences.iter().flat_map(|x| ['i', '+', '*', '(', ')'].map(|ch| format!("{x}{ch}"))).collect();
+        for sentence in &sentences {
+            assert_eq!(original.parse(sentence).1.is_some(), transformed.parse(sentence).1.is_some(), "{sentence}");
+        }
+    }
+    let gnf = PushDownAutomatonGrammar::build_with_case("S->aSb|$".to_string(), 'S').expect("err").to_greibach_normal_form().expect("gnf");
+    println!("{gnf}");
+    assert_eq!(gnf.check_greibach_normal_form(), Ok(()));
+    assert!(gnf.production_set()[&gnf.start()].contains("$"));
+}
+
+#[test]
+fn test_earley_parser() {
+    //左递归且二义的文法无需变换即可分析
+    let parser = EarleyParser::new(&PushDownAutomatonGrammar::build_with_case("E->E+E|E*E|(E)|i".to_string(), 'E').expect("err"));
+    let (chart, trees) = parser.parse("i+i*i", 8);
+    println!("{chart}");
+    trees.iter().for_each(|x| println!("{x}"));
+    assert_eq!(trees.len(), 2);
+    assert_eq!(parser.parse("(i+i)*i", 8).1.len(), 1);
+    assert_eq!(parser.parse("i+i+i+i", 8).1.len(), 5);
+    assert_eq!(parser.parse("i+i+i+i", 3).1.len(), 3);
+    assert!(parser.parse("i+", 8).1.is_empty());
+    //含空产生式与可空非终结符
+    let parser = EarleyParser::new(&PushDownAutomatonGrammar::build_with_case("S->AB|aSb,A->aA|$,B->b|$".to_string(), 'S').expect("err"));
+    for (sentence, accepted) in [("", true), ("aab", true), ("ab", true), ("abb", true), ("ba", false)] {
+        assert_eq!(!parser.parse(sentence, 8).1.is_empty(), accepted, "{sentence}");
+    }
+}
+
+#[test]
+fn test_lr0_automaton() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->aA|bB,A->cA|d,B->cB|d".to_string(), 'E').expect("err");
+    let automaton = LrAutomaton::build_lr0(&grammar).expect("lr0");
+    println!("{}", automaton.grammar());
+    println!("{automaton}");
+    let (dfa, _) = automaton.to_dfa().expect("dfa");
+    //教材中的例子共有12个项目集
+    assert_eq!(dfa.state().len(), 12);
+    for (prefix, is_handle) in [("acc", false), ("accd", true), ("bB", true), ("E", true), ("ab", false)] {
+        assert_eq!(dfa.accept(prefix), is_handle, "{prefix}");
+    }
+}
+
+#[test]
+fn test_slr_parser() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->E+T|T,T->T*F|F,F->(E)|i".to_string(), 'E').expect("err");
+    let (automaton, table) = LrParsingTable::build_slr1(&grammar).expect("slr");
+    println!("{table}");
+    assert_eq!(automaton.states().len(), 12);
+    assert!(table.conflicts().is_empty());
+    for (sentence, accept) in [("i+i*i", true), ("(i+i)*i", true), ("i", true), ("i+", false), ("(i", false), ("", false)] {
+        match table.analyzer(sentence) {
+            Ok(steps) => {
+                assert!(accept, "{sentence}");
+                steps.iter().for_each(|x| println!("{x}"));
+            }
+            Err(_) => assert!(!accept, "{sentence}"),
+        }
+    }
+    //S->L=R在SLR(1)下存在移进-归约冲突
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->L=R|R,L->*R|i,R->L".to_string(), 'S').expect("err");
+    let (_, table) = LrParsingTable::build_slr1(&grammar).expect("slr");
+    table.conflicts().iter().for_each(|x| print!("{x}"));
+    assert_eq!(table.conflicts().len(), 1);
+    assert!(table.conflicts()[0].is_shift_reduce());
+}
+
+#[test]
+fn test_lr1_and_lalr1_parser() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->L=R|R,L->*R|i,R->L".to_string(), 'S').expect("err");
+    let (automaton, table) = LrParsingTable::build_lr1(&grammar).expect("lr1");
+    println!("{automaton}");
+    assert_eq!(automaton.states().len(), 14);
+    assert!(table.conflicts().is_empty());
+    let (automaton, table) = LrParsingTable::build_lalr1(&grammar).expect("lalr1");
+    println!("{table}");
+    assert_eq!(automaton.states().len(), 10);
+    assert!(table.conflicts().is_empty());
+    for (sentence, accept) in [("*i=i", true), ("i", true), ("**i=*i", true), ("i=", false), ("=i", false)] {
+        assert_eq!(table.analyzer(sentence).is_ok(), accept, "{sentence}");
+    }
+    //LR(1)文法，但合并同心项目集后产生归约-归约冲突
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->aAd|bBd|aBe|bAe,A->c,B->c".to_string(), 'S').expect("err");
+    let (_, table) = LrParsingTable::build_lr1(&grammar).expect("lr1");
+    assert!(table.conflicts().is_empty());
+    assert!(table.analyzer("bce").is_ok());
+    let (_, table) = LrParsingTable::build_lalr1(&grammar).expect("lalr1");
+    table.conflicts().iter().for_each(|x| print!("{x}"));
+    assert_eq!(table.conflicts().len(), 2);
+    assert!(table.conflicts().iter().all(|x| x.is_introduced_by_merge() && !x.is_shift_reduce()));
+}
+
+#[test]
+fn test_operator_precedence_parser() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->E+T|T,T->T*F|F,F->(E)|i".to_string(), 'E').expect("err");
+    assert_eq!(grammar.get_firstvt_set()[&'E'], HashSet::from(['+', '*', '(', 'i']));
+    assert_eq!(grammar.get_lastvt_set()[&'T'], HashSet::from(['*', ')', 'i']));
+    let parser = OperatorPrecedenceParser::new(&grammar).expect("op");
+    println!("{parser}");
+    assert!(parser.conflicts().is_empty());
+    assert_eq!(parser.relation('+', '*'), Some(PrecedenceRelation::Less));
+    assert_eq!(parser.relation('*', '+'), Some(PrecedenceRelation::Greater));
+    assert_eq!(parser.relation('(', ')'), Some(PrecedenceRelation::Equal));
+    assert_eq!(parser.relation('i', 'i'), None);
+    for (sentence, accept) in [("i+i*i", true), ("(i+i)*i", true), ("i", true), ("ii", false), ("i+", false), ("()", false)] {
+        match parser.analyzer(sentence) {
+            Ok(steps) => {
+                assert!(accept, "{sentence}");
+                steps.iter().for_each(|x| println!("{x}"));
+            }
+            Err(_) => assert!(!accept, "{sentence}"),
+        }
+    }
+    //二义的表达式文法中+与+之间既⋖又⋗
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->E+E|E*E|i".to_string(), 'E').expect("err");
+    assert!(!OperatorPrecedenceParser::new(&grammar).expect("op").conflicts().is_empty());
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->EF|i,F->i".to_string(), 'E').expect("err");
+    assert!(grammar.check_operator_grammar().is_err());
+}
+
+#[test]
+fn test_lr_precedence_declarations() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->E+E|E*E|E^E|E=E|(E)|i".to_string(), 'E').expect("err");
+    let (_, mut table) = LrParsingTable::build_lalr1(&grammar).expect("lalr1");
+    assert_eq!(table.conflicts().len(), 16);
+    assert!(table.resolve_conflicts(&PrecedenceDeclarations::default()).0.is_empty());
+    let declarations = PrecedenceDeclarations::parse("nonassoc:=;left:+;left:*;right:^").expect("precedence");
+    let (resolved, unresolved) = table.resolve_conflicts(&declarations);
+    assert!(unresolved.is_empty());
+    resolved.iter().for_each(|x| println!("{x}"));
+    assert_eq!(resolved.len(), 16);
+    assert!(table.conflicts().is_empty());
+    let reduced = |sentence: &str| table.analyzer(sentence).expect(sentence).into_iter().filter(|x| x.contains("归约")).map(|x| x.split("用").nth(1).unwrap().to_string()).collect::<Vec<_>>();
+    //i+i*i 先归约E*E，i*i+i 先归约E*E
+    assert!(reduced("i+i*i").iter().position(|x| x.contains("E->E*E")) < reduced("i+i*i").iter().position(|x| x.contains("E->E+E")));
+    assert!(reduced("i*i+i").iter().position(|x| x.contains("E->E*E")) < reduced("i*i+i").iter().position(|x| x.contains("E->E+E")));
+    //+左结合，^右结合：i+i+i 的第一次二元归约发生在读入第二个+之前，i^i^i 则在全部移进之后
+    assert!(table.analyzer("i+i+i").expect("left").iter().any(|x| x.contains("+i#") && x.contains("E->E+E")));
+    assert!(!table.analyzer("i^i^i").expect("right").iter().any(|x| x.contains("^i#") && x.contains("E->E^E")));
+    //=无结合，连续比较不可接受
+    assert!(table.analyzer("i=i").is_ok());
+    assert!(table.analyzer("i=i=i").is_err());
+    assert!(PrecedenceDeclarations::parse("up:+").is_err());
+    //,本身也可以作为运算符声明优先级
+    let grammar = PushDownAutomatonGrammar::build_with_ebnf(r#"E ::= E "," E | E "+" E | "i" ;"#, None).expect("err");
+    let (_, mut table) = LrParsingTable::build_slr1(&grammar).expect("slr");
+    let (resolved, unresolved) = table.resolve_conflicts(&PrecedenceDeclarations::parse("left:,\nleft:+").expect("precedence"));
+    assert_eq!((resolved.len(), unresolved.len()), (4, 0));
+    assert!(table.analyzer("i,i+i").is_ok());
+    //一个移进与两个归约的冲突无法按优先级解决，需给出原因
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->Ac|Bc|acc,A->a,B->a".to_string(), 'S').expect("err");
+    let (_, mut table) = LrParsingTable::build_slr1(&grammar).expect("slr");
+    let (resolved, unresolved) = table.resolve_conflicts(&PrecedenceDeclarations::parse("left:c").expect("precedence"));
+    unresolved.iter().for_each(|x| println!("{x}"));
+    assert!(resolved.is_empty());
+    assert_eq!(unresolved.len(), 1);
+    assert!(unresolved[0].contains("同时存在多个归约"));
+    assert_eq!(table.conflicts().len(), 1);
+}
+
+#[test]
+fn test_prediction_analyzer_parse_tree() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->TU,U->+TU|$,T->FV,V->*FV|$,F->(E)|i".to_string(), 'E').expect("err");
+    let (analyzer, _, _, _) = grammar.build_ll1_analyzer().expect("ll1");
+    let (_, tree) = analyzer.analyzer("i+i").expect("accept");
+    let leaf = ParseTree::leaf;
+    let empty_v = || ParseTree::new('V', vec![leaf('$')]);
+    let term = || ParseTree::new('T', vec![ParseTree::new('F', vec![leaf('i')]), empty_v()]);
+    let expected = ParseTree::new('E', vec![
+        term(),
+        ParseTree::new('U', vec![leaf('+'), term(), ParseTree::new('U', vec![leaf('$')])]),
+    ]);
+    assert_eq!(tree, expected);
+    println!("{tree}");
+    let dot = tree.to_dot();
+    println!("{dot}");
+    assert!(dot.starts_with("digraph ParseTree {"));
+    assert!(dot.contains("node0 [label=\"E\"];"));
+    assert!(dot.contains("node0 -> node1;"));
+    assert_eq!(dot.matches("->").count(), 14);
+}
+
+#[test]
+fn test_analysis_step_table() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->TU,U->+TU|$,T->FV,V->*FV|$,F->(E)|i".to_string(), 'E').expect("err");
+    let (analyzer, _, _, _) = grammar.build_ll1_analyzer().expect("ll1");
+    let (steps, _) = analyzer.analyzer("i+i").expect("accept");
+    let text = render_analysis_steps(&steps, StepTableFormat::Text);
+    println!("{text}");
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), steps.len() + 1);
+    assert_eq!(lines[1].split_whitespace().collect::<Vec<_>>(), ["1", "#E", "i+i#", "E->TU"]);
+    assert_eq!(lines[3].split_whitespace().collect::<Vec<_>>(), ["3", "#UVF", "i+i#", "F->i"]);
+    assert_eq!(lines[4].split_whitespace().collect::<Vec<_>>(), ["4", "#UVi", "i+i#", "i匹配"]);
+    assert_eq!(lines.last().unwrap().split_whitespace().collect::<Vec<_>>(), [steps.len().to_string().as_str(), "#", "#", "接受"]);
+    //剩余输入串右对齐
+    assert!(lines[1..].iter().all(|x| x.rfind('#') == lines[1].rfind('#')));
+    let markdown = render_analysis_steps(&steps, StepTableFormat::Markdown);
+    println!("{markdown}");
+    assert_eq!(markdown.lines().nth(2).unwrap(), "| 1 | #E | i+i# | E->TU |");
+    let csv = render_analysis_steps(&steps, StepTableFormat::Csv);
+    println!("{csv}");
+    assert_eq!(csv.lines().next().unwrap(), "步骤,分析栈,剩余输入串,所用产生式");
+    assert_eq!(csv.lines().nth(1).unwrap(), "1,#E,i+i#,E->TU");
+}
+
+#[test]
+fn test_prediction_analyzer_syntax_error() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->TU,U->+TU|$,T->FV,V->*FV|$,F->(E)|i".to_string(), 'E').expect("err");
+    let (analyzer, _, _, _) = grammar.build_ll1_analyzer().expect("ll1");
+    //+后缺少操作数，此时栈顶为T
+    let err = analyzer.analyzer("i+*i").expect_err("reject");
+    println!("{err}");
+    assert_eq!(err.position(), 2);
+    assert!(err.to_string().contains("M[T,*]"));
+    assert!(err.to_string().contains("{( i}"));
+    //缺少右括号，栈顶终结符)与#不匹配
+    let err = analyzer.analyzer("(i").expect_err("reject");
+    println!("{err}");
+    assert_eq!(err.position(), 2);
+    assert!(err.to_string().contains("{)}"));
+    //多余的输入
+    let err = analyzer.analyzer("i)").expect_err("reject");
+    println!("{err}");
+    assert_eq!(err.position(), 1);
+}
+
+#[test]
+fn test_prediction_analyzer_error_recovery() {
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->TU,U->+TU|$,T->FV,V->*FV|$,F->(E)|i".to_string(), 'E').expect("err");
+    let (analyzer, _, follow, _) = grammar.build_ll1_analyzer().expect("ll1");
+    let analyzer = analyzer.with_synch_set(&follow);
+    println!("{analyzer}");
+    //*后缺少操作数，以及缺少右括号
+    let (steps, tree, errors) = analyzer.analyzer_with_recovery("i*+i+(i");
+    println!("{}", render_analysis_steps(&steps, StepTableFormat::Text));
+    println!("{tree}");
+    errors.iter().for_each(|x| println!("{x}"));
+    assert_eq!(errors.iter().map(|x| x.position()).collect::<Vec<_>>(), [2, 7]);
+    assert!(errors[0].to_string().contains("M[F,+]"));
+    assert!(tree.to_string().lines().filter(|x| x.trim() == "i").count() == 3);
+    //连续的运算符与+后缺少操作数
+    let (_, _, errors) = analyzer.analyzer_with_recovery("i**i+)i");
+    assert_eq!(errors.iter().map(|x| x.position()).collect::<Vec<_>>(), [2, 5]);
+    //无错误时与analyzer一致
+    let (steps, tree, errors) = analyzer.analyzer_with_recovery("(i+i)*i");
+    assert!(errors.is_empty());
+    assert_eq!(Ok((steps, tree)), analyzer.analyzer("(i+i)*i"));
+}
+
+#[test]
+fn test_ll1_conflicts() {
+    //S->aA|aB 为FIRST/FIRST冲突；A->bA|$ 中的b同时属于FOLLOW(A)，为FIRST/FOLLOW冲突
+    let grammar = PushDownAutomatonGrammar::build_with_case("S->aAb|aBc,A->bA|$,B->c".to_string(), 'S').expect("err");
+    let conflicts = grammar.get_ll1_conflicts().expect("conflicts");
+    conflicts.iter().for_each(|x| println!("{x}"));
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts.iter().filter(|x| x.kind() == Ll1ConflictKind::FirstFirst).count(), 1);
+    assert!(conflicts.iter().any(|x| x.to_string().starts_with("M[A,b] 存在FIRST/FOLLOW冲突")));
+    assert!(conflicts.iter().any(|x| x.to_string().starts_with("M[S,a] 存在FIRST/FIRST冲突")));
+    //build_ll1_analyzer的错误信息中包含全部冲突
+    let message = grammar.build_ll1_analyzer().expect_err("not ll1");
+    assert_eq!(message.lines().count(), 2);
+    let left_recursive = PushDownAutomatonGrammar::build_with_case("E->E+i|i".to_string(), 'E').expect("err");
+    assert!(left_recursive.get_ll1_conflicts().is_err());
+}
+
+#[test]
+fn test_multi_char_grammar_symbols() {
+    let grammar = PushDownAutomatonGrammar::build_with_symbols("E->T E',E'->+ T E'|$,T->F T',T'->* F T'|$,F->( E )|id", "E").expect("err");
+    let symbols = grammar.symbol_table();
+    let code = |x: &str| symbols.code(x).expect("symbol");
+    assert_eq!(grammar.terminal().len(), 5);
+    assert_eq!(grammar.non_terminal().len(), 5);
+    assert!(grammar.to_string().starts_with("E -> T E'\n"));
+    let (analyzer, first, follow, _) = grammar.build_ll1_analyzer().expect("ll1");
+    assert_eq!(symbols.show_set(&first[&code("T")]), "{(, id}");
+    assert_eq!(symbols.show_set(&first[&code("E'")]), "{$, +}");
+    assert_eq!(symbols.show_set(&follow[&code("T'")]), "{#, ), +}");
+    let (steps, tree) = analyzer.analyzer("id + id * id").expect("accept");
+    assert!(render_analysis_steps(&steps, StepTableFormat::Text).contains("T'->* F T'"));
+    assert!(tree.to_string().starts_with("E\n  T\n    F\n      id\n"));
+    let err = analyzer.analyzer("id + * id").expect_err("reject");
+    assert_eq!(err.position(), 5);
+    assert!(err.to_string().contains("{( id}"));
+    //单字符的名字同样可以使用空格分隔的写法，且名字的大小写不再决定符号的种类
+    let grammar = PushDownAutomatonGrammar::build_with_symbols("s->a s b|$", "s").expect("err");
+    assert!(grammar.build_ll1_analyzer().expect("ll1").0.analyzer("aabb").is_ok());
+    assert!(PushDownAutomatonGrammar::build_with_symbols("E->T", "S").is_err());
+    //只有给出--symbols时才按空格分隔符号
+    let args = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>().into_iter();
+    let grammar = build_push_down_automaton_grammar_with_args(args(&["--grammar", "E -> E + T | T, T -> id, unused -> u", "--start", "E", "--symbols"]));
+    assert_eq!(grammar.terminal().len(), 3);
+    assert!(std::panic::catch_unwind(|| build_push_down_automaton_grammar_with_args(args(&["--grammar", "E->E+T | T", "--start", "E"]))).is_err());
+    //按单个字符显示符号的分析器拒绝多字符的文法符号
+    assert!(LrParsingTable::build_slr1(&grammar).is_err());
+    assert!(CykParser::new(&grammar).is_err());
+    assert!(OperatorPrecedenceParser::new(&grammar).is_err());
+    assert!(grammar.to_pda().expect_err("multi char").contains("多字符"));
+    //错误信息中的符号按名字显示
+    let recursive = PushDownAutomatonGrammar::build_with_symbols("expr->expr + id|id", "expr").expect("err");
+    assert_eq!(recursive.build_ll1_analyzer().expect_err("left recursion"), "在计算非终结符expr 时发现左递归 递归集合如下 : {expr}");
+    assert!(recursive.check_greibach_normal_form().expect_err("not gnf").starts_with("expr->"));
+    let (_, report) = grammar.reduce();
+    assert_eq!(report, ["删除unused：从开始符号E不可达", "删除终结符u：从开始符号E不可达"]);
+}
+
+#[test]
+fn test_ebnf_grammar_file() {
+    let text = r#"
+        # 表达式文法
+        expr   ::= term { ("+" | "-") term } ;
+        term   ::= factor { "*" factor }
+        factor ::= "(" expr ")" | id | num [ "!" ]   (* 可选的阶乘 *)
+    "#;
+    let grammar = PushDownAutomatonGrammar::build_with_ebnf(text, None).expect("err");
+    println!("{grammar}");
+    let symbols = grammar.symbol_table();
+    assert_eq!(grammar.start(), symbols.code("expr").expect("start"));
+    for helper in ["expr_rep", "expr_grp", "term_rep", "factor_opt"] {
+        assert!(grammar.non_terminal().contains(&symbols.code(helper).expect(helper)));
+    }
+    assert_eq!(grammar.terminal().len(), 8);
+    let (analyzer, _, _, _) = grammar.build_ll1_analyzer().expect("ll1");
+    assert!(analyzer.analyzer("num ! * ( id - num ) + id").is_ok());
+    assert!(analyzer.analyzer("id + * id").is_err());
+    //BNF写法，<name>为非终结符，空的候选式即空串
+    let bnf = "<list> ::= <item> <rest>\n<rest> ::= ',' <item> <rest> |\n<item> ::= 'a' | 'b'";
+    let grammar = PushDownAutomatonGrammar::build_with_ebnf(bnf, Some("list")).expect("err");
+    let (analyzer, _, _, _) = grammar.build_ll1_analyzer().expect("ll1");
+    assert!(analyzer.analyzer("a , b , a").is_ok());
+    assert!(PushDownAutomatonGrammar::build_with_ebnf("s ::= ( a | b", None).expect_err("unclosed").starts_with("第1行"));
+    assert!(PushDownAutomatonGrammar::build_with_ebnf("s ::= a\nt ::= b )", None).expect_err("extra").starts_with("第2行"));
+    //从文件读入的文法交给Earley分析，项目与语法树按符号的名字显示，输入以空格分隔
+    let path = std::env::temp_dir().join("test_ebnf_grammar_file.ebnf");
+    std::fs::write(&path, r#"expr ::= term { "+" term } ; term ::= id ;"#).expect("write");
+    let grammar = load_grammar_file(path.to_str().unwrap(), None).expect("load");
+    std::fs::remove_file(&path).ok();
+    let parser = EarleyParser::new(&grammar);
+    let (chart, trees) = parser.parse(&grammar.symbol_table().encode("id + id").expect("encode"), 8);
+    assert_eq!(trees.len(), 1);
+    assert!(trees[0].to_string().starts_with("expr\n  term\n    id\n  expr_rep\n    +\n"));
+    assert!(chart.to_string().contains("[expr->term·expr_rep, 0]"));
+    assert!(chart.to_string().contains("(已读入id + id)"));
+    assert!(grammar.symbol_table().encode("id+id").is_err());
+    assert!(grammar.symbol_table().encode("id term").is_err());
+    //仍按单字符处理符号的分析器给出明确的错误
+    assert!(LrParsingTable::build_slr1(&grammar).expect_err("multi char").contains("多字符"));
+    //符号均为单字符时辅助非终结符使用未占用的大写字母，文件中的文法可交给LR与CYK分析
+    std::fs::write(&path, r#"E ::= T { "+" T } ; T ::= "i" ;"#).expect("write");
+    let grammar = load_grammar_file(path.to_str().unwrap(), None).expect("load");
+    std::fs::remove_file(&path).ok();
+    assert!(!grammar.symbol_table().is_multi_char());
+    let (_, table) = LrParsingTable::build_slr1(&grammar).expect("slr");
+    assert!(table.conflicts().is_empty());
+    assert!(table.analyzer("i+i+i").is_ok() && table.analyzer("i+").is_err());
+    let parser = CykParser::new(&grammar).expect("cyk");
+    assert!(parser.parse("i+i").1.is_some() && parser.parse("+i").1.is_none());
+}
+
+#[test]
+fn test_token_stream_input() {
+    let grammar = PushDownAutomatonGrammar::build_with_symbols(
+        "P->S P|$,S->id := E ;|while E do S,E->T E',E'->+ T E'|$,T->id|num|( E )", "P").expect("err");
+    let (analyzer, _, follow, _) = grammar.build_ll1_analyzer().expect("ll1");
+    let analyzer = analyzer.with_synch_set(&follow);
+    let lexer = Lexer::new(&grammar);
+    let source = "x := y + 42;\nwhile x do\n  x := (x+1.5) ;";
+    let tokens = lexer.tokenize(source);
+    assert_eq!(tokens.iter().map(|x| x.kind()).collect::<Vec<_>>(),
+               ["id", ":=", "id", "+", "num", ";", "while", "id", "do", "id", ":=", "(", "id", "+", "num", ")", ";"]);
+    assert_eq!((tokens[4].lexeme(), tokens[4].position()), ("42", 9));
+    //源程序末尾只剩运算符的前缀时不能越界匹配
+    assert_eq!(lexer.tokenize("x :").iter().map(|x| x.kind()).collect::<Vec<_>>(), ["id", ":"]);
+    assert!(analyzer.analyzer_tokens(&tokens).is_ok());
+    //出错位置为记号在源程序中的位置
+    let source = "x := 1;\ny := + 2;";
+    let err = analyzer.analyzer_tokens(&lexer.tokenize(source)).expect_err("reject");
+    assert_eq!(line_column(source, err.position()), (2, 6));
+    let (_, _, errors) = analyzer.analyzer_tokens_with_recovery(&lexer.tokenize("x := 1 y := 2;"));
+    assert_eq!(errors.len(), 1);
+    //单字符文法通过种类映射接受标识符与数字
+    let grammar = PushDownAutomatonGrammar::build_with_case("E->TU,U->+TU|$,T->FV,V->*FV|$,F->(E)|i".to_string(), 'E').expect("err");
+    let kind_map = Lexer::parse_kind_map("id:i, num:i").expect("map");
+    let lexer = Lexer::new(&grammar).with_kind_map(kind_map);
+    let analyzer = grammar.build_ll1_analyzer().expect("ll1").0;
+    assert!(analyzer.analyzer_tokens(&lexer.tokenize("alpha + 3 * (beta+gamma)")).is_ok());
+    assert!(analyzer.analyzer_tokens(&lexer.tokenize("alpha beta")).is_err());
+    assert!(Lexer::parse_kind_map("id").is_err());
+}
+
+#[test]
+fn test_chomsky_hierarchy() {
+    let classify = |grammar: &str, start: char| ChomskyClassification::classify(grammar, start).expect("err");
+    let right = classify("S->aA|b,A->aS|bB|$,B->b", 'S');
+    assert_eq!(right.grammar_type(), ChomskyType::Type3(Linearity::Right));
+    assert!(is_deterministic_right_linear("S->aA|b,A->aS|bB|$,B->b"));
+    assert!(!is_deterministic_right_linear("S->aA|aB,A->a,B->b"));
+    assert_eq!(classify("S->Ab|a,A->Sa|b", 'S').grammar_type(), ChomskyType::Type3(Linearity::Left));
+    //左右线性混用、右部过长均不是3型文法
+    let mixed = classify("S->aA|b,A->Sb|a", 'S');
+    assert_eq!(mixed.grammar_type(), ChomskyType::Type2);
+    assert!(mixed.to_string().contains("混用"));
+    let context_free = classify("S->aSb|$", 'S');
+    assert_eq!(context_free.grammar_type(), ChomskyType::Type2);
+    assert!(context_free.to_string().contains("S->aSb 的右部不是"));
+    //a^n b^n c^n
+    let context_sensitive = classify("S->aSBC|aBC,CB->BC,aB->ab,bB->bb,bC->bc,cC->cc", 'S');
+    assert_eq!(context_sensitive.grammar_type(), ChomskyType::Type1);
+    assert_eq!(context_sensitive.to_string().lines().count(), 6);
+    let unrestricted = classify("S->aSB|$,aB->Ba,B->$", 'S');
+    assert_eq!(unrestricted.grammar_type(), ChomskyType::Type0);
+    assert!(unrestricted.to_string().contains("B->$ 为非开始符号的空产生式"));
+    assert!(unrestricted.to_string().contains("开始符号S出现在产生式右部"));
+    assert!(ChomskyClassification::classify("ab->c", 'S').is_err());
+    assert!(ChomskyClassification::classify("A->a", 'S').is_err());
+    //按字符分类，不接受空格分隔的多字符符号
+    assert!(ChomskyClassification::classify("S -> a S b | $", 'S').is_err());
+    let args = |list: &[&str]| list.iter().map(|x| x.to_string()).collect::<Vec<_>>().into_iter();
+    assert_eq!(read_grammar_args(args(&["--grammar", "S->aS|b", "--start", "S"])), ("S->aS|b".to_string(), 'S'));
+    let empty_start = std::panic::catch_unwind(|| read_grammar_args(args(&["--grammar", "S->a", "--start", ""])));
+    let message = empty_start.expect_err("空的开始符号应当报错");
+    assert_eq!(message.downcast_ref::<String>().map(String::as_str), Some("excepted value of param --start"));
+}
+
+#[test]
+fn test_sentence_generator() {
+    let balanced = PushDownAutomatonGrammar::build_with_case("S->aSb|$".to_string(), 'S').expect("err");
+    assert_eq!(balanced.enumerate_sentences(4, 10).expect("enumerate"), ["$", "ab", "aabb"]);
+    let expression = PushDownAutomatonGrammar::build_with_case("E->E+T|T,T->T*F|F,F->(E)|i".to_string(), 'E').expect("err");
+    assert_eq!(expression.enumerate_sentences(3, 10).expect("enumerate"), ["i", "(i)", "i*i", "i+i"]);
+    assert_eq!(expression.enumerate_sentences(5, 3).expect("enumerate").len(), 3);
+    //凑够limit个句子即停止，不会展开所有长度不超过max_length的句型
+    let ambiguous = PushDownAutomatonGrammar::build_with_case("S->SS|a|b".to_string(), 'S').expect("err");
+    assert_eq!(ambiguous.enumerate_sentences(40, 4).expect("enumerate"), ["a", "b", "aa", "ab"]);
+    assert_eq!(balanced.enumerate_sentences(0, 10).expect("enumerate"), ["$"]);
+    let derivations = expression.get_derivations("i+i*i").expect("derive");
+    assert_eq!(derivations.leftmost().join(" => "), "E => E+T => T+T => F+T => i+T => i+T*F => i+F*F => i+i*F => i+i*i");
+    assert_eq!(derivations.rightmost().join(" => "), "E => E+T => E+T*F => E+T*i => E+F*i => E+i*i => T+i*i => F+i*i => i+i*i");
+    assert!(!derivations.is_ambiguous());
+    assert!(expression.get_derivations("i+").is_err());
+    let ambiguous = PushDownAutomatonGrammar::build_with_case("E->E+E|i".to_string(), 'E').expect("err");
+    assert!(ambiguous.get_derivations("i+i+i").expect("derive").is_ambiguous());
+    assert_eq!(balanced.get_derivations("$").expect("derive").leftmost(), ["S", "$"]);
+    //随机生成的句子均属于该语言，深度限制越小句子越短
+    let mut random = XorShiftRandom::new(2024);
+    for _ in 0..20 {
+        let sentence = expression.generate_random_sentence(6, &mut random).expect("generate");
+        assert!(expression.get_derivations(&sentence).is_ok(), "{sentence}");
+    }
+    assert_eq!(expression.generate_random_sentence(0, &mut random).expect("generate"), "i");
+    let useless = PushDownAutomatonGrammar::build_with_case("S->aS".to_string(), 'S').expect("err");
+    assert!(useless.generate_random_sentence(5, &mut random).is_err());
+    //多字符符号的推导以空格分隔
+    let grammar = PushDownAutomatonGrammar::build_with_symbols("E->T E',E'->+ T E'|$,T->id", "E").expect("err");
+    assert_eq!(grammar.get_derivations("id + id").expect("derive").leftmost().last().expect("last"), "id + id");
+    assert_eq!(grammar.enumerate_sentences(3, 10).expect("enumerate"), ["id", "id + id"]);
+}
@@ -1,5 +1,7 @@
+use std::collections::HashSet;
+
 use crate::deterministic_finite_automaton::State;
-use crate::statics::GRAMMAR_SPLIT_IO_UNIT;
+use crate::statics::{EMPTY_SENTENCE, GRAMMAR_SPLIT_IO_UNIT};
 
 pub fn collect_ordered_vec<Item: Ord, T: IntoIterator<Item=Item>>(iter: T) -> Vec<Item> {
     let mut vec = iter.into_iter().collect::<Vec<_>>();
@@ -19,3 +21,26 @@ pub fn split_type_two_grammar(grammar: String) -> Result<(State, String), ()> {
         Err(())
     };
 }
+
+pub fn fresh_non_terminal(used: &HashSet<char>) -> Option<char> {
+    //优先使用未被占用的大写字母，用尽后使用其他Unicode大写字母
+    ('A'..='Z').chain((0x391..=0xFFFF).filter_map(char::from_u32).filter(|x| x.is_uppercase()))
+        .find(|x| !used.contains(x))
+}
+
+pub fn split_production(production: &str) -> Vec<char> {
+    //将产生式右部拆为符号序列，空串对应空序列
+    if production == EMPTY_SENTENCE {
+        Vec::new()
+    } else {
+        production.chars().collect()
+    }
+}
+
+pub fn join_production(symbols: &[char]) -> String {
+    if symbols.is_empty() {
+        EMPTY_SENTENCE.to_string()
+    } else {
+        symbols.iter().collect()
+    }
+}